@@ -0,0 +1,58 @@
+//! Activity heatmaps for the minimap and the hierarchy tree: the transitions of a signal,
+//! or of every signal under a scope, counted in equal time spans over the whole trace.
+
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{iter_range, SignalData};
+use crate::value::same_value;
+
+/// Most buckets in a heatmap, well above the width of a minimap in pixels.
+pub(crate) const MAX_BUCKETS: usize = 4096;
+
+/// What a heatmap counts the transitions of.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivitySource {
+    Signal { signal_id: usize },
+    /// Every signal under a scope, at any depth, or of the whole file for `None`. A signal
+    /// shared by several vars counts once.
+    Scope {
+        #[serde(default)]
+        scope_ref: Option<usize>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActivityMap {
+    /// The buckets split `[start, end]` into equal spans, from 0 to the end of the trace.
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    /// Transitions in each bucket, summed over the signals.
+    pub(crate) transitions: Vec<usize>,
+    /// Number of signals counted.
+    pub(crate) signals: usize,
+}
+
+impl ActivityMap {
+    pub(crate) fn new(end: u64, buckets: usize) -> Self {
+        ActivityMap { start: 0, end, transitions: vec![0; buckets], signals: 0 }
+    }
+
+    /// Counts the transitions of a signal. Changes that repeat the previous value and
+    /// the signal's first value are no transitions.
+    pub(crate) fn add(&mut self, signal: SignalData, time_table: &[Time]) {
+        let span = (self.end - self.start) as u128 + 1;
+        let buckets = self.transitions.len() as u128;
+        let mut previous = None;
+        for (time_idx, value) in iter_range(signal, 0..signal.time_indices().len()) {
+            if previous.as_ref().is_some_and(|previous| !same_value(previous, &value)) {
+                let time = time_table[time_idx as usize].clamp(self.start, self.end);
+                let bucket = (time - self.start) as u128 * buckets / span;
+                self.transitions[bucket as usize] += 1;
+            }
+            previous = Some(value);
+        }
+        self.signals += 1;
+    }
+}
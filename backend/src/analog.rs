@@ -0,0 +1,178 @@
+//! Numeric windows of real signals, and of bit vectors read as unsigned numbers, for
+//! plotting analog traces such as those of mixed-signal simulations.
+
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{iter_range, window_range, SignalData};
+use crate::value;
+
+/// How the UI draws between two samples.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Hold each value until the next change, as simulators produce them.
+    #[default]
+    Step,
+    /// Draw a straight line to the next sample, for sampled analog quantities.
+    Linear,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnalogSample {
+    pub(crate) time: u64,
+    /// `None` where a bit vector has unknown bits.
+    pub(crate) value: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnalogWindow {
+    pub(crate) interpolation: Interpolation,
+    /// Range of the values shown in the window, to scale the plot. `None` when none is known.
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    /// True when `samples` only keeps the extremes of each span of the window.
+    pub(crate) decimated: bool,
+    /// Like the changes of a window, they start with the sample giving the value at the
+    /// window start and end with the one after the window.
+    pub(crate) samples: Vec<AnalogSample>,
+}
+
+/// Reads the samples of `[start, end]`. Past `max_samples`, the window is cut into equal
+/// spans of which only the lowest and highest samples are kept, in time order, so that
+/// peaks survive at any zoom level.
+pub(crate) fn analog_window(
+    signal: SignalData,
+    time_table: &[Time],
+    start: u64,
+    end: u64,
+    max_samples: Option<usize>,
+    interpolation: Interpolation,
+) -> AnalogWindow {
+    let range = window_range(signal, time_table, start, end);
+    let samples = iter_range(signal, range.clone()).map(|(time_idx, value)| AnalogSample {
+        time: time_table[time_idx as usize],
+        value: value::to_f64(&value),
+    });
+
+    let mut visible = VisibleRange { start, end, held: None, range: None };
+    let mut extend_range = |sample: &AnalogSample| visible.add(sample);
+
+    let decimate = max_samples.filter(|max_samples| range.len() > *max_samples);
+    let samples = match decimate {
+        None => samples.inspect(&mut extend_range).collect(),
+        Some(max_samples) => {
+            // Two samples per span, plus those around the window
+            let buckets = (max_samples.saturating_sub(2) / 2).max(1);
+            let span = end.saturating_sub(start) as u128;
+            let bucket_of = |time: u64| {
+                let offset = time.saturating_sub(start) as u128;
+                (offset * buckets as u128).checked_div(span).map_or(buckets - 1, |bucket| (bucket as usize).min(buckets - 1))
+            };
+
+            let mut out = Vec::with_capacity(max_samples);
+            let mut bucket: Option<(usize, Vec<AnalogSample>)> = None;
+            for sample in samples {
+                extend_range(&sample);
+                if sample.time < start || sample.time > end {
+                    flush(&mut out, bucket.take());
+                    out.push(sample);
+                    continue;
+                }
+                let index = bucket_of(sample.time);
+                match &mut bucket {
+                    Some((current, held)) if *current == index => held.push(sample),
+                    _ => {
+                        flush(&mut out, bucket.take());
+                        bucket = Some((index, vec![sample]));
+                    }
+                }
+            }
+            flush(&mut out, bucket);
+            out
+        }
+    };
+
+    let range = visible.finish();
+    AnalogWindow {
+        interpolation,
+        min: range.map(|(min, _)| min),
+        max: range.map(|(_, max)| max),
+        decimated: decimate.is_some(),
+        samples,
+    }
+}
+
+/// Range of the known values visible in `[start, end]`, fed with the window's samples in order.
+struct VisibleRange {
+    start: u64,
+    end: u64,
+    /// Value of the last sample before `start`, visible unless a change lands on `start`.
+    held: Option<f64>,
+    range: Option<(f64, f64)>,
+}
+
+impl VisibleRange {
+    fn add(&mut self, sample: &AnalogSample) {
+        if sample.time < self.start {
+            self.held = sample.value;
+            return;
+        }
+        let held = self.held.take();
+        if sample.time > self.start {
+            self.include(held);
+        }
+        if sample.time <= self.end {
+            self.include(sample.value);
+        }
+    }
+
+    fn include(&mut self, value: Option<f64>) {
+        if let Some(value) = value {
+            self.range = Some(self.range.map_or((value, value), |(min, max)| (min.min(value), max.max(value))));
+        }
+    }
+
+    fn finish(mut self) -> Option<(f64, f64)> {
+        let held = self.held.take();
+        self.include(held);
+        self.range
+    }
+}
+
+/// Keeps the lowest and highest samples of a span, in time order. A span with unknown
+/// values keeps its first unknown one, so that the gap still shows.
+fn flush(out: &mut Vec<AnalogSample>, bucket: Option<(usize, Vec<AnalogSample>)>) {
+    let Some((_, samples)) = bucket else { return };
+    let known = || samples.iter().enumerate().filter_map(|(i, sample)| Some((i, sample.value?)));
+    let lowest = known().min_by(|a, b| a.1.total_cmp(&b.1)).map(|(i, _)| i);
+    let highest = known().max_by(|a, b| a.1.total_cmp(&b.1)).map(|(i, _)| i);
+    let unknown = samples.iter().position(|sample| sample.value.is_none());
+
+    let mut keep: Vec<usize> = [lowest, highest, unknown].into_iter().flatten().collect();
+    keep.sort_unstable();
+    keep.dedup();
+    let mut samples: Vec<Option<AnalogSample>> = samples.into_iter().map(Some).collect();
+    out.extend(keep.into_iter().filter_map(|i| samples[i].take()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: u64, value: Option<f64>) -> AnalogSample {
+        AnalogSample { time, value }
+    }
+
+    #[test]
+    fn test_flush_keeps_extremes_in_time_order() {
+        let mut out = Vec::new();
+        let span = vec![sample(1, Some(2.0)), sample(2, Some(9.0)), sample(3, Some(-1.0)), sample(4, Some(3.0))];
+        flush(&mut out, Some((0, span)));
+        assert_eq!(out, vec![sample(2, Some(9.0)), sample(3, Some(-1.0))]);
+
+        out.clear();
+        flush(&mut out, Some((0, vec![sample(5, None), sample(6, Some(1.0)), sample(7, None)])));
+        assert_eq!(out, vec![sample(5, None), sample(6, Some(1.0))]);
+    }
+}
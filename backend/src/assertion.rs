@@ -0,0 +1,488 @@
+//! Checks SystemVerilog-style assertions against a dump after the fact. The supported
+//! subset looks like
+//!
+//! ```text
+//! @(posedge top.clk) disable iff (top.rst) top.req ##1 !top.busy |-> ##[1:4] $rose(top.ack)
+//! ```
+//!
+//! - a clocking event, `posedge` or `negedge` of an expression, which is required
+//! - an optional `disable iff (expr)`, which cancels every attempt during which the
+//!   expression is true at any time, not only at ticks
+//! - sequences of expressions (see the `expression` module) joined by `##n` or `##[m:n]`
+//!   delays in ticks, where `$` as `n` waits until the end of the trace. A sequence may
+//!   start with a delay. Each attempt of an open-ended `##[m:$]` may scan to the end of
+//!   the trace, so checking one takes time quadratic in the number of ticks.
+//! - an optional implication, `|->` starting the consequent at the tick where the
+//!   antecedent matches, or `|=>` starting it one tick later
+//!
+//! Expressions are sampled just before each tick, so a register updated on the clock edge
+//! is seen with its old value, and `$rose`, `$fell`, `$stable` and `$past` compare with the
+//! previous tick. Every tick starts an attempt. With an implication, attempts whose
+//! antecedent does not match are vacuous and not reported.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wellen::{Time, TimeTableIdx};
+
+use crate::changes::{iter_range, SignalData};
+use crate::evaluate::{self, four_state};
+use crate::expression::{Expression, ResolvedSignal};
+use crate::logic;
+use crate::value::{STATE_0, STATE_1};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptStatus {
+    Pass,
+    Fail,
+    /// The disable condition was true during the attempt.
+    Disabled,
+    /// The trace ends before the attempt can pass or fail.
+    Incomplete,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AssertionAttempt {
+    /// Tick where the attempt starts.
+    pub start: u64,
+    /// Tick where it passes or fails, the last tick of the trace when incomplete.
+    pub end: u64,
+    pub status: AttemptStatus,
+}
+
+/// Fields are public so that command line tools can report on them.
+#[derive(Serialize, Deserialize, Default)]
+pub struct AssertionReport {
+    pub attempts: Vec<AssertionAttempt>,
+    pub passed: usize,
+    pub failed: usize,
+    pub disabled: usize,
+    pub incomplete: usize,
+}
+
+/// One expression of a sequence, matched `min..=max` ticks after the previous one.
+struct Step {
+    min: usize,
+    max: Option<usize>,
+    term: usize,
+}
+
+pub(crate) struct Property {
+    clock: Expression,
+    /// State the clock goes to at a tick.
+    edge: u8,
+    disable: Option<Expression>,
+    /// The antecedent and the number of ticks between its end and the consequent's start.
+    antecedent: Option<(Vec<Step>, usize)>,
+    consequent: Vec<Step>,
+    terms: Vec<Expression>,
+}
+
+/// A piece of a property body, between the top level operators.
+enum Piece<'t> {
+    Term(&'t str),
+    Delay(usize, Option<usize>),
+    Implication(usize),
+}
+
+impl Property {
+    pub(crate) fn parse(
+        text: &str,
+        resolve: impl Fn(&str, Option<(i64, i64)>) -> Result<ResolvedSignal, String>,
+    ) -> Result<Self, String> {
+        let parse = |text: &str| Expression::parse(text, &resolve);
+        let unclocked = |text: &str| {
+            let expression = parse(text)?;
+            if expression.is_sampled() {
+                return Err("$rose, $fell, $stable and $past cannot be used in the clock or disable condition".to_string());
+            }
+            Ok(expression)
+        };
+
+        let rest = text
+            .trim_start()
+            .strip_prefix('@')
+            .ok_or("Assertions need a sampling clock, as in @(posedge clk)")?;
+        let (event, rest) = parenthesized(rest)?;
+        let event = event.trim_start();
+        let (edge, clock) = if let Some(clock) = keyword(event, "posedge") {
+            (STATE_1, clock)
+        } else if let Some(clock) = keyword(event, "negedge") {
+            (STATE_0, clock)
+        } else {
+            return Err(format!("Clocking event must be posedge or negedge, found '{}'", event));
+        };
+        let clock = unclocked(clock)?;
+
+        let (disable, rest) = match keyword(rest.trim_start(), "disable") {
+            Some(rest) => {
+                let rest = keyword(rest.trim_start(), "iff").ok_or("Expected 'iff' after 'disable'")?;
+                let (condition, rest) = parenthesized(rest)?;
+                (Some(unclocked(condition)?), rest)
+            }
+            None => (None, rest),
+        };
+
+        let pieces = split_body(rest)?;
+        let mut sequences = pieces.split(|piece| matches!(piece, Piece::Implication(_)));
+        let mut terms = Vec::new();
+        let mut sequence = |pieces: &[Piece]| -> Result<Vec<Step>, String> {
+            let mut steps = Vec::new();
+            let mut delay = None;
+            for piece in pieces {
+                match piece {
+                    Piece::Term(text) => {
+                        let (min, max) = delay.take().unwrap_or((0, Some(0)));
+                        terms.push(parse(text)?);
+                        steps.push(Step { min, max, term: terms.len() - 1 });
+                    }
+                    Piece::Delay(..) if delay.is_some() => return Err("Expected an expression between delays".to_string()),
+                    Piece::Delay(min, max) => delay = Some((*min, *max)),
+                    Piece::Implication(_) => unreachable!(),
+                }
+            }
+            if steps.is_empty() || delay.is_some() {
+                return Err("Sequence must end with an expression".to_string());
+            }
+            Ok(steps)
+        };
+        let first = sequence(sequences.next().unwrap_or_default())?;
+        let (antecedent, consequent) = match sequences.next() {
+            Some(second) => {
+                let offset = pieces
+                    .iter()
+                    .find_map(|piece| match piece {
+                        Piece::Implication(offset) => Some(*offset),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                (Some((first, offset)), sequence(second)?)
+            }
+            None => (None, first),
+        };
+        if sequences.next().is_some() {
+            return Err("A property can only have one implication".to_string());
+        }
+
+        Ok(Property { clock, edge, disable, antecedent, consequent, terms })
+    }
+
+    /// Every signal the property reads.
+    pub(crate) fn signal_ids(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = std::iter::once(&self.clock)
+            .chain(&self.disable)
+            .chain(&self.terms)
+            .flat_map(|expression| expression.operands())
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// The rest of `text` after `word`, when `word` is followed by a space or parenthesis.
+fn keyword<'t>(text: &'t str, word: &str) -> Option<&'t str> {
+    text.strip_prefix(word).filter(|rest| rest.starts_with(|c: char| c.is_whitespace() || c == '('))
+}
+
+/// Splits `(inside) rest` at the matching parenthesis.
+fn parenthesized(text: &str) -> Result<(&str, &str), String> {
+    let text = text.trim_start();
+    if !text.starts_with('(') {
+        return Err("Expected '('".to_string());
+    }
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&text[1..i], &text[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err("Expected ')' at end of property".to_string())
+}
+
+/// Splits a property body at delays and implications outside of brackets.
+fn split_body(text: &str) -> Result<Vec<Piece<'_>>, String> {
+    fn push_term<'t>(pieces: &mut Vec<Piece<'t>>, term: &'t str) {
+        if !term.trim().is_empty() {
+            pieces.push(Piece::Term(term.trim()));
+        }
+    }
+
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut term_start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if depth == 0 && (rest.starts_with("|->") || rest.starts_with("|=>")) {
+            push_term(&mut pieces, &text[term_start..i]);
+            pieces.push(Piece::Implication(if rest.starts_with("|->") { 0 } else { 1 }));
+            i += 3;
+            term_start = i;
+            continue;
+        }
+        if depth == 0 && rest.starts_with("##") {
+            push_term(&mut pieces, &text[term_start..i]);
+            let (min, max, length) = delay(&rest[2..])?;
+            pieces.push(Piece::Delay(min, max));
+            i += 2 + length;
+            term_start = i;
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    push_term(&mut pieces, &text[term_start..]);
+    Ok(pieces)
+}
+
+/// Parses `n` or `[m:n]` after `##`, returning the range and the length of its text.
+fn delay(text: &str) -> Result<(usize, Option<usize>, usize), String> {
+    let skipped = text.len() - text.trim_start().len();
+    let text = text.trim_start();
+    let invalid = || format!("Invalid delay: ##{}", text.split_whitespace().next().unwrap_or_default());
+    let number = |digits: &str| digits.trim().parse::<usize>().map_err(|_| invalid());
+    if let Some(range) = text.strip_prefix('[') {
+        let end = range.find(']').ok_or_else(invalid)?;
+        let (min, max) = range[..end].split_once(':').ok_or_else(invalid)?;
+        let min = number(min)?;
+        let max = match max.trim() {
+            "$" => None,
+            max => Some(number(max)?),
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(invalid());
+        }
+        return Ok((min, max, skipped + end + 2));
+    }
+    let length = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let count = number(&text[..length])?;
+    Ok((count, Some(count), skipped + length))
+}
+
+/// Runs every attempt of `property`. `sources` holds the changes of every signal in
+/// `Property::signal_ids`. With `failures_only`, passing, disabled and incomplete attempts
+/// are counted but not listed.
+pub(crate) fn check(
+    property: &Property,
+    sources: &HashMap<usize, SignalData>,
+    time_table: &[Time],
+    failures_only: bool,
+) -> AssertionReport {
+    let bind = |expression: &Expression| expression.operands().iter().map(|id| sources[id]).collect::<Vec<_>>();
+    let mut ticks = Vec::new();
+    let mut previous = None;
+    for (time_idx, value) in evaluate::stream(&property.clock, &bind(&property.clock), 0) {
+        let state = logic::truth(&value);
+        if state == property.edge && previous.is_some_and(|previous| previous != property.edge) {
+            ticks.push(time_idx);
+        }
+        previous = Some(state);
+    }
+    let truths = sample_terms(&property.terms, sources, &ticks);
+
+    let mut disable_changes: Vec<(TimeTableIdx, bool)> = Vec::new();
+    if let Some(disable) = &property.disable {
+        for (time_idx, value) in evaluate::stream(disable, &bind(disable), 0) {
+            let active = logic::truth(&value) == STATE_1;
+            if disable_changes.last().is_none_or(|(_, last)| *last != active) {
+                disable_changes.push((time_idx, active));
+            }
+        }
+    }
+    let disabled_between = |from: TimeTableIdx, to: TimeTableIdx| {
+        let first = disable_changes.partition_point(|(idx, _)| *idx <= from).saturating_sub(1);
+        disable_changes[first..].iter().take_while(|(idx, _)| *idx <= to).any(|(_, active)| *active)
+    };
+
+    let mut report = AssertionReport::default();
+    for start in 0..ticks.len() {
+        let (status, end) = match &property.antecedent {
+            Some((antecedent, offset)) => {
+                let matched = match_sequence(antecedent, &truths, start);
+                if matched.ends.is_empty() {
+                    continue;
+                }
+                // Every match of the antecedent needs a match of the consequent
+                let outcomes: Vec<_> = matched
+                    .ends
+                    .iter()
+                    .map(|end| obligation(&property.consequent, &truths, end + offset))
+                    .collect();
+                let ends = |status| outcomes.iter().filter(move |(s, _)| *s == status).map(|(_, end)| *end);
+                if let Some(end) = ends(AttemptStatus::Fail).min() {
+                    (AttemptStatus::Fail, end)
+                } else if ends(AttemptStatus::Incomplete).next().is_some() {
+                    (AttemptStatus::Incomplete, ticks.len() - 1)
+                } else {
+                    (AttemptStatus::Pass, ends(AttemptStatus::Pass).max().unwrap_or(start))
+                }
+            }
+            None => obligation(&property.consequent, &truths, start),
+        };
+        let status = if disabled_between(ticks[start], ticks[end]) { AttemptStatus::Disabled } else { status };
+
+        match status {
+            AttemptStatus::Pass => report.passed += 1,
+            AttemptStatus::Fail => report.failed += 1,
+            AttemptStatus::Disabled => report.disabled += 1,
+            AttemptStatus::Incomplete => report.incomplete += 1,
+        }
+        if !failures_only || status == AttemptStatus::Fail {
+            report.attempts.push(AssertionAttempt {
+                start: time_table[ticks[start] as usize],
+                end: time_table[ticks[end] as usize],
+                status,
+            });
+        }
+    }
+    report
+}
+
+/// Whether each term is true (1) at each tick, sampled just before the tick.
+fn sample_terms(terms: &[Expression], sources: &HashMap<usize, SignalData>, ticks: &[TimeTableIdx]) -> Vec<Vec<bool>> {
+    // Every signal is sampled once per tick, then handed to the terms that use it
+    let mut ids: Vec<(usize, usize)> = terms
+        .iter()
+        .flat_map(|term| term.operands().iter().copied().zip(term.operand_widths().iter().copied()))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut cursors: Vec<_> = ids
+        .iter()
+        .map(|(id, _)| iter_range(sources[id], 0..sources[id].time_indices().len()).peekable())
+        .collect();
+    let mut values: Vec<Vec<u8>> = ids.iter().map(|(_, width)| logic::unknown(*width)).collect();
+    let positions: Vec<Vec<usize>> = terms
+        .iter()
+        .map(|term| term.operands().iter().map(|id| ids.iter().position(|(i, _)| i == id).unwrap()).collect())
+        .collect();
+    let mut previous: Vec<Vec<Vec<u8>>> = positions
+        .iter()
+        .map(|positions| positions.iter().map(|p| logic::unknown(ids[*p].1)).collect())
+        .collect();
+
+    let mut truths = vec![Vec::with_capacity(ticks.len()); terms.len()];
+    for tick in ticks {
+        for ((cursor, value), (_, width)) in cursors.iter_mut().zip(values.iter_mut()).zip(&ids) {
+            while let Some((_, change)) = cursor.next_if(|(idx, _)| idx < tick) {
+                *value = four_state(&change, *width);
+            }
+        }
+        for (i, term) in terms.iter().enumerate() {
+            let operands: Vec<Vec<u8>> = positions[i].iter().map(|p| values[*p].clone()).collect();
+            truths[i].push(logic::truth(&term.evaluate_sampled(&operands, &previous[i])) == STATE_1);
+            previous[i] = operands;
+        }
+    }
+    truths
+}
+
+struct SequenceMatch {
+    /// Ticks where the sequence matches, in order.
+    ends: Vec<usize>,
+    /// Last tick looked at.
+    last: usize,
+    /// Some delay window ran past the end of the trace.
+    truncated: bool,
+}
+
+fn match_sequence(steps: &[Step], truths: &[Vec<bool>], start: usize) -> SequenceMatch {
+    let ticks = truths[0].len();
+    let mut positions = vec![start];
+    let mut last = start;
+    let mut truncated = false;
+    for step in steps {
+        // Positions are in order, so each window only needs scanning past the previous one
+        let mut next = Vec::new();
+        let mut scanned = 0;
+        for position in &positions {
+            let high = step.max.map_or(usize::MAX, |max| position.saturating_add(max));
+            if high >= ticks {
+                truncated = true;
+            }
+            let (low, high) = (position.saturating_add(step.min).max(scanned), high.min(ticks - 1));
+            if low > high {
+                continue;
+            }
+            next.extend((low..=high).filter(|tick| truths[step.term][*tick]));
+            scanned = high.saturating_add(1);
+            last = last.max(high);
+        }
+        positions = next;
+        if positions.is_empty() {
+            break;
+        }
+    }
+    SequenceMatch { ends: positions, last, truncated }
+}
+
+/// Status of matching `steps` from `start` and the tick where it is decided. The first
+/// match passes.
+fn obligation(steps: &[Step], truths: &[Vec<bool>], start: usize) -> (AttemptStatus, usize) {
+    let last_tick = truths[0].len() - 1;
+    if start > last_tick {
+        return (AttemptStatus::Incomplete, last_tick);
+    }
+    let matched = match_sequence(steps, truths, start);
+    match matched.ends.first() {
+        Some(end) => (AttemptStatus::Pass, *end),
+        None if matched.truncated => (AttemptStatus::Incomplete, last_tick),
+        None => (AttemptStatus::Fail, matched.last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_body() {
+        let pieces = split_body("a[1] ##[1:$] (b |-> c) |=> ##2 {d, e}").unwrap();
+        let described: Vec<String> = pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Term(text) => text.to_string(),
+                Piece::Delay(min, max) => format!("##{}:{:?}", min, max),
+                Piece::Implication(offset) => format!("imp{}", offset),
+            })
+            .collect();
+        assert_eq!(described, ["a[1]", "##1:None", "(b |-> c)", "imp1", "##2:Some(2)", "{d, e}"]);
+        assert_eq!(split_body("a ##[3:1] b").err().as_deref(), Some("Invalid delay: ##[3:1]"));
+    }
+
+    #[test]
+    fn test_match_sequence() {
+        // a ##[1:2] b over ticks where a = 1100, b = 0011
+        let truths = vec![vec![true, true, false, false], vec![false, false, true, true]];
+        let steps = [Step { min: 0, max: Some(0), term: 0 }, Step { min: 1, max: Some(2), term: 1 }];
+        let from_first = match_sequence(&steps, &truths, 0);
+        assert_eq!(from_first.ends, vec![2]);
+        assert!(!from_first.truncated);
+        assert_eq!(match_sequence(&steps, &truths, 1).ends, vec![2, 3]);
+        assert_eq!(obligation(&steps, &truths, 2), (AttemptStatus::Fail, 2));
+        assert_eq!(obligation(&steps[1..], &truths, 3), (AttemptStatus::Incomplete, 3));
+
+        // Delays past the end of any trace are incomplete rather than overflowing
+        let huge = [Step { min: 0, max: Some(0), term: 0 }, Step { min: 1, max: Some(usize::MAX), term: 1 }];
+        let from_first = match_sequence(&huge, &truths, 0);
+        assert_eq!(from_first.ends, vec![2, 3]);
+        assert!(from_first.truncated);
+        let beyond = [Step { min: usize::MAX, max: Some(usize::MAX), term: 1 }];
+        assert_eq!(obligation(&beyond, &truths, 1), (AttemptStatus::Incomplete, 3));
+    }
+}
@@ -0,0 +1,74 @@
+//! Checks assertions against a waveform file without the UI, for example on the dump of a
+//! failing regression run:
+//!
+//! ```text
+//! check-assertions dump.fst '@(posedge top.clk) top.req |-> ##[1:4] top.ack'
+//! check-assertions dump.fst --file assertions.txt
+//! ```
+//!
+//! A file holds one property per line; empty lines and lines starting with `//` are
+//! skipped. The exit status is 0 when no attempt fails, 1 when one does and 2 on errors.
+
+use std::process::ExitCode;
+
+use backend::{add_file_bytes, check_assertion};
+
+const USAGE: &str = "Usage: check-assertions <waveform> [--file <properties>] [<property>...]";
+
+fn properties(args: &[String]) -> Result<Vec<String>, String> {
+    let mut properties = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--file" {
+            let path = args.next().ok_or(USAGE)?;
+            let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            properties.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with("//"))
+                    .map(str::to_string),
+            );
+        } else {
+            properties.push(arg.clone());
+        }
+    }
+    if properties.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(properties)
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    let (path, rest) = args.split_first().ok_or(USAGE)?;
+    let properties = properties(rest)?;
+    let content = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let filename = add_file_bytes(path.clone(), content)?;
+
+    let mut all_passed = true;
+    for property in &properties {
+        let report = check_assertion(filename.clone(), property.clone(), true).map_err(|e| format!("{}: {}", property, e))?;
+        let verdict = if report.failed == 0 { "PASS" } else { "FAIL" };
+        println!(
+            "{} {} ({} passed, {} failed, {} disabled, {} incomplete)",
+            verdict, property, report.passed, report.failed, report.disabled, report.incomplete
+        );
+        for attempt in &report.attempts {
+            println!("  failed: started at {}, failed at {}", attempt.start, attempt.end);
+        }
+        all_passed &= report.failed == 0;
+    }
+    Ok(all_passed)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Buses dumped as separate single-bit vars (`data[0]` … `data[31]`), as netlist-level
+//! dumps often have them, and named fields of wide buses. Both become virtual signals
+//! built from expressions over the file's vars.
+
+use serde::{Deserialize, Serialize};
+use wellen::{Hierarchy, ScopeRef, VarRef};
+
+/// A named `[msb:lsb]` range of a bus, in the bus's declared indices.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BusField {
+    pub name: String,
+    pub msb: i64,
+    pub lsb: i64,
+}
+
+/// Sibling vars `name[n]` of one bit each, covering a contiguous range of indices.
+pub(crate) struct BitGroup {
+    /// `None` for vars declared outside of any scope.
+    pub(crate) scope: Option<ScopeRef>,
+    pub(crate) name: String,
+    pub(crate) msb: i64,
+    pub(crate) lsb: i64,
+    /// The bits, msb first.
+    pub(crate) bits: Vec<VarRef>,
+}
+
+/// Finds the groups of single-bit vars in every scope. A name that is also declared as
+/// a var without a bit index, or whose indices leave gaps, is not grouped.
+pub(crate) fn find_bit_groups(hierarchy: &Hierarchy) -> Vec<BitGroup> {
+    let scopes = (0..hierarchy.iter_scopes().len()).filter_map(ScopeRef::from_index);
+    let mut groups = group_vars(hierarchy, None, hierarchy.vars().collect());
+    for scope_ref in scopes {
+        groups.extend(group_vars(hierarchy, Some(scope_ref), hierarchy[scope_ref].vars(hierarchy).collect()));
+    }
+    groups
+}
+
+fn group_vars(hierarchy: &Hierarchy, scope: Option<ScopeRef>, vars: Vec<VarRef>) -> Vec<BitGroup> {
+    // Names in declaration order, each with its bits
+    let mut candidates: Vec<(&str, Vec<(i64, VarRef)>)> = Vec::new();
+    let mut excluded: Vec<&str> = Vec::new();
+    for var_ref in vars {
+        let var = &hierarchy[var_ref];
+        let name = var.name(hierarchy);
+        match var.index().filter(|index| index.msb() == index.lsb() && var.length() == Some(1)) {
+            Some(index) => match candidates.iter_mut().find(|(candidate, _)| *candidate == name) {
+                Some((_, bits)) => bits.push((index.msb(), var_ref)),
+                None => candidates.push((name, vec![(index.msb(), var_ref)])),
+            },
+            None => excluded.push(name),
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter(|(name, bits)| bits.len() > 1 && !excluded.contains(name))
+        .filter_map(|(name, mut bits)| {
+            bits.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+            let contiguous = bits.windows(2).all(|pair| pair[0].0 == pair[1].0 + 1);
+            contiguous.then(|| BitGroup {
+                scope,
+                name: name.to_string(),
+                msb: bits[0].0,
+                lsb: bits[bits.len() - 1].0,
+                bits: bits.into_iter().map(|(_, var_ref)| var_ref).collect(),
+            })
+        })
+        .collect()
+}
+
+/// The path of a var as expressions refer to it. Single-bit vars keep their index, which
+/// tells the bits of a split bus apart. Names that are not plain identifiers are escaped.
+pub(crate) fn var_path(hierarchy: &Hierarchy, var_ref: VarRef) -> String {
+    let var = &hierarchy[var_ref];
+    let path = var
+        .full_name(hierarchy)
+        .split('.')
+        .map(|part| {
+            let plain = part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+            if plain { part.to_string() } else { format!("\\{} ", part) }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+    match var.index() {
+        Some(index) if index.msb() == index.lsb() => format!("{}[{}]", path, index.msb()),
+        _ => path,
+    }
+}
+
+/// `{a, b, ...}`, or the single part alone.
+pub(crate) fn concatenation(parts: &[String]) -> String {
+    match parts {
+        [part] => part.clone(),
+        _ => format!("{{{}}}", parts.join(", ")),
+    }
+}
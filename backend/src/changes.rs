@@ -0,0 +1,77 @@
+use std::ops::Range;
+
+use wellen::{Signal, SignalValue, Time, TimeTableIdx};
+
+use crate::virtual_signal::VirtualSignal;
+
+/// Changes of either a signal from the file or a virtual signal computed by the backend.
+#[derive(Clone, Copy)]
+pub(crate) enum SignalData<'a> {
+    Loaded(&'a Signal),
+    Virtual(&'a VirtualSignal),
+}
+
+impl<'a> SignalData<'a> {
+    pub(crate) fn time_indices(&self) -> &'a [TimeTableIdx] {
+        match self {
+            SignalData::Loaded(signal) => signal.time_indices(),
+            SignalData::Virtual(signal) => signal.time_indices(),
+        }
+    }
+}
+
+/// Value of the change with the given position in the signal's change list.
+pub(crate) fn value_at(signal: SignalData<'_>, index: usize) -> Option<SignalValue<'_>> {
+    iter_range(signal, index..index + 1).next().map(|(_, value)| value)
+}
+
+/// Iterates the changes with positions in `range` without walking the ones before it.
+pub(crate) fn iter_range(
+    signal: SignalData<'_>,
+    range: Range<usize>,
+) -> impl DoubleEndedIterator<Item = (TimeTableIdx, SignalValue<'_>)> + '_ {
+    let time_indices = signal.time_indices();
+    let range = range.start.min(time_indices.len())..range.end.min(time_indices.len());
+    // wellen only hands out data offsets through a time lookup. Any offset can be pointed
+    // at a single change by adjusting its start, so one lookup serves the whole range.
+    let template = match signal {
+        SignalData::Loaded(signal) => time_indices
+            .get(range.start)
+            .and_then(|time_idx| signal.get_offset(*time_idx)),
+        SignalData::Virtual(_) => None,
+    };
+    range.filter_map(move |index| {
+        let value = match signal {
+            SignalData::Loaded(signal) => {
+                let mut offset = template.clone()?;
+                offset.start = index;
+                offset.elements = 1;
+                signal.get_value_at(&offset, 0)
+            }
+            SignalData::Virtual(signal) => signal.value(index),
+        };
+        Some((time_indices[index], value))
+    })
+}
+
+/// Position of the first change at or after `time` within `time_indices`.
+pub(crate) fn first_change_at_or_after(time_indices: &[TimeTableIdx], time_table: &[Time], time: Time) -> usize {
+    let table_idx = time_table.partition_point(|t| *t < time);
+    time_indices.partition_point(|idx| (*idx as usize) < table_idx)
+}
+
+/// Position of the first change strictly after `time` within `time_indices`.
+pub(crate) fn first_change_after(time_indices: &[TimeTableIdx], time_table: &[Time], time: Time) -> usize {
+    let table_idx = time_table.partition_point(|t| *t <= time);
+    time_indices.partition_point(|idx| (*idx as usize) < table_idx)
+}
+
+/// Positions of the changes reported for the window `[start, end]`: every change inside
+/// it, plus the change just before it, which gives the value at `start`, and the one
+/// just after it, which lets the UI draw up to `end`.
+pub(crate) fn window_range(signal: SignalData<'_>, time_table: &[Time], start: Time, end: Time) -> Range<usize> {
+    let time_indices = signal.time_indices();
+    let first = first_change_at_or_after(time_indices, time_table, start);
+    let last = first_change_after(time_indices, time_table, end);
+    first.saturating_sub(1)..(last + 1).min(time_indices.len())
+}
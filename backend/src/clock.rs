@@ -0,0 +1,130 @@
+//! Detection of clock-like signals: 1-bit signals that rise at a regular period with a
+//! regular duty cycle. Spans where such a signal stops toggling are reported as gated
+//! rather than disqualifying it.
+
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{iter_range, SignalData};
+use crate::value;
+
+/// Fewer rising edges than this cannot tell a clock from a few pulses.
+const MIN_RISING_EDGES: usize = 4;
+/// A period this many times the typical one means the clock stopped in between.
+const GATED_RATIO: f64 = 1.5;
+/// Largest spread of the per-cycle duty cycles of a clock.
+const DUTY_TOLERANCE: f64 = 0.2;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GatedInterval {
+    /// Last edge before the clock stopped, the rising one when it stopped high.
+    pub(crate) start: u64,
+    /// Next rising edge, or the end of the trace when the clock never restarts.
+    pub(crate) end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClockInfo {
+    pub(crate) signal_id: usize,
+    pub(crate) path: String,
+    /// Mean time between rising edges while running, in file time units.
+    pub(crate) period: f64,
+    /// In Hz, when the file has a known timescale.
+    pub(crate) frequency: Option<f64>,
+    /// Share of each running period spent high.
+    pub(crate) duty_cycle: f64,
+    /// Shortest and longest periods while running; their difference is the jitter.
+    pub(crate) min_period: u64,
+    pub(crate) max_period: u64,
+    /// Number of rising edges.
+    pub(crate) cycles: usize,
+    pub(crate) gated: Vec<GatedInterval>,
+}
+
+/// Analyses a 1-bit signal and returns `None` when it does not behave like a clock.
+/// `end_time` is the end of the trace and `seconds_per_unit` the file's timescale.
+pub(crate) fn analyze(
+    signal_id: usize,
+    path: String,
+    signal: SignalData,
+    time_table: &[Time],
+    end_time: u64,
+    seconds_per_unit: Option<f64>,
+) -> Option<ClockInfo> {
+    let mut rises = Vec::new();
+    let mut falls = Vec::new();
+    let mut previous: Option<bool> = None;
+    for (time_idx, value) in iter_range(signal, 0..signal.time_indices().len()) {
+        let bit = match value::bit_states(&value).as_deref() {
+            Some([state]) => value::state_to_bit(*state),
+            _ => return None,
+        };
+        let time = time_table[time_idx as usize];
+        match (previous, bit) {
+            (Some(false), Some(true)) => rises.push(time),
+            (Some(true), Some(false)) => falls.push(time),
+            _ => {}
+        }
+        previous = bit;
+    }
+    if rises.len() < MIN_RISING_EDGES {
+        return None;
+    }
+
+    let periods: Vec<u64> = rises.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mut sorted = periods.clone();
+    sorted.sort_unstable();
+    let typical = sorted[sorted.len() / 2] as f64;
+    if periods.iter().any(|period| (*period as f64) < typical / GATED_RATIO) {
+        return None;
+    }
+
+    let fall_after = |time: u64| falls.get(falls.partition_point(|fall| *fall <= time)).copied();
+    let mut gated = Vec::new();
+    let (mut cycles, mut running_time, mut high_time) = (0, 0, 0);
+    let (mut min_duty, mut max_duty) = (f64::MAX, f64::MIN);
+    let (mut min_period, mut max_period) = (u64::MAX, 0);
+    for (rise, period) in rises.iter().zip(&periods) {
+        let next_rise = rise + period;
+        // Exactly one falling edge per period, which starts the gap when the clock stops
+        let fall = fall_after(*rise).filter(|fall| *fall < next_rise)?;
+        if fall_after(fall).is_some_and(|next_fall| next_fall < next_rise) {
+            return None;
+        }
+        if *period as f64 > typical * GATED_RATIO {
+            let start = if (fall - rise) as f64 > typical { *rise } else { fall };
+            gated.push(GatedInterval { start, end: next_rise });
+            continue;
+        }
+        let duty = (fall - rise) as f64 / *period as f64;
+        min_duty = min_duty.min(duty);
+        max_duty = max_duty.max(duty);
+        min_period = min_period.min(*period);
+        max_period = max_period.max(*period);
+        cycles += 1;
+        running_time += period;
+        high_time += fall - rise;
+    }
+    if cycles == 0 || max_duty - min_duty > DUTY_TOLERANCE {
+        return None;
+    }
+
+    let last_rise = rises[rises.len() - 1];
+    if (end_time - last_rise) as f64 > typical * GATED_RATIO {
+        let start = fall_after(last_rise).unwrap_or(last_rise);
+        gated.push(GatedInterval { start, end: end_time });
+    }
+
+    let period = running_time as f64 / cycles as f64;
+    Some(ClockInfo {
+        signal_id,
+        path,
+        period,
+        frequency: seconds_per_unit.map(|seconds| 1.0 / (period * seconds)),
+        duty_cycle: high_time as f64 / running_time as f64,
+        min_period,
+        max_period,
+        cycles: rises.len(),
+        gated,
+    })
+}
@@ -0,0 +1,118 @@
+//! Time axis counted in cycles of a reference clock. Cycle `n` starts at the clock's
+//! `n`th edge, counting from 0, and positions between two edges are fractional. Before
+//! the first edge and after the last one, the nearest period is extended.
+
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{iter_range, SignalData};
+use crate::value;
+
+/// The clock edges that start a cycle.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockEdge {
+    #[default]
+    Rising,
+    Falling,
+    /// Both edges, as for double data rate interfaces.
+    Both,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CycleAxisInfo {
+    /// Number of clock edges, the last of which starts the last cycle.
+    pub(crate) cycles: usize,
+    pub(crate) first_edge: u64,
+    pub(crate) last_edge: u64,
+}
+
+pub(crate) struct CycleAxis {
+    /// Times of the edges, increasing.
+    edges: Vec<u64>,
+}
+
+impl CycleAxis {
+    /// Collects the edges of a 1-bit clock. Like edge navigation, an edge is a change to 1
+    /// (rising) or 0 (falling) from any other value, the first value of the signal aside.
+    pub(crate) fn build(clock: SignalData, time_table: &[Time], edge: ClockEdge) -> Result<Self, String> {
+        let mut edges = Vec::new();
+        let mut previous: Option<Option<bool>> = None;
+        for (time_idx, value) in iter_range(clock, 0..clock.time_indices().len()) {
+            let bit = match value::bit_states(&value).as_deref() {
+                Some([state]) => value::state_to_bit(*state),
+                _ => return Err("The cycle axis needs a 1-bit clock".to_string()),
+            };
+            let is_edge = match (previous, bit) {
+                (Some(previous), Some(level)) if previous != Some(level) => match edge {
+                    ClockEdge::Rising => level,
+                    ClockEdge::Falling => !level,
+                    ClockEdge::Both => true,
+                },
+                _ => false,
+            };
+            if is_edge {
+                edges.push(time_table[time_idx as usize]);
+            }
+            previous = Some(bit);
+        }
+        if edges.len() < 2 {
+            return Err("The clock needs at least two edges to define cycles".to_string());
+        }
+        Ok(CycleAxis { edges })
+    }
+
+    pub(crate) fn edges(&self) -> &[u64] {
+        &self.edges
+    }
+
+    pub(crate) fn info(&self) -> CycleAxisInfo {
+        CycleAxisInfo {
+            cycles: self.edges.len(),
+            first_edge: self.edges[0],
+            last_edge: self.edges[self.edges.len() - 1],
+        }
+    }
+
+    /// Index of the edge starting the cycle or the extended period that holds `time`.
+    fn segment(&self, time: f64) -> usize {
+        let after = self.edges.partition_point(|edge| (*edge as f64) <= time);
+        after.clamp(1, self.edges.len() - 1) - 1
+    }
+
+    pub(crate) fn cycle_at(&self, time: u64) -> f64 {
+        let index = self.segment(time as f64);
+        let (start, end) = (self.edges[index] as f64, self.edges[index + 1] as f64);
+        index as f64 + (time as f64 - start) / (end - start)
+    }
+
+    /// The time of a cycle position, rounded to the nearest time unit and never negative.
+    pub(crate) fn time_at(&self, cycle: f64) -> u64 {
+        let index = (cycle.floor().max(0.0) as usize).min(self.edges.len() - 2);
+        let (start, end) = (self.edges[index] as f64, self.edges[index + 1] as f64);
+        (start + (cycle - index as f64) * (end - start)).round().max(0.0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_time_mapping() {
+        let axis = CycleAxis { edges: vec![10, 30, 40, 60] };
+        assert_eq!(axis.cycle_at(10), 0.0);
+        assert_eq!(axis.cycle_at(20), 0.5);
+        assert_eq!(axis.cycle_at(35), 1.5);
+        assert_eq!(axis.cycle_at(70), 3.5);
+        assert_eq!(axis.cycle_at(0), -0.5);
+
+        assert_eq!(axis.time_at(0.5), 20);
+        assert_eq!(axis.time_at(1.5), 35);
+        assert_eq!(axis.time_at(3.5), 70);
+        assert_eq!(axis.time_at(-2.0), 0);
+        for time in [0, 10, 17, 30, 45, 60, 65] {
+            assert_eq!(axis.time_at(axis.cycle_at(time)), time);
+        }
+    }
+}
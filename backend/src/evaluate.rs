@@ -0,0 +1,65 @@
+//! Evaluation of expressions along the merged change streams of their operands.
+
+use std::iter::Peekable;
+
+use wellen::{SignalValue, TimeTableIdx};
+
+use crate::changes::{iter_range, SignalData};
+use crate::expression::Expression;
+use crate::logic;
+use crate::value::bit_states;
+
+/// Yields the value of an expression at every time one of its operands changes, from a
+/// given time table index on. The first item, at `from`, folds in every earlier change.
+/// Consecutive items may hold the same value.
+pub(crate) struct ExpressionStream<'e, I: Iterator> {
+    expression: &'e Expression,
+    from: TimeTableIdx,
+    cursors: Vec<Peekable<I>>,
+    values: Vec<Vec<u8>>,
+}
+
+/// `sources` holds the changes of each operand, in the order of `Expression::operands`.
+pub(crate) fn stream<'a, 'e>(
+    expression: &'e Expression,
+    sources: &[SignalData<'a>],
+    from: TimeTableIdx,
+) -> ExpressionStream<'e, impl Iterator<Item = (TimeTableIdx, SignalValue<'a>)> + 'a> {
+    let cursors = sources
+        .iter()
+        .map(|source| {
+            // Start at the change that holds the value at `from`
+            let time_indices = source.time_indices();
+            let first = time_indices.partition_point(|idx| *idx <= from).saturating_sub(1);
+            iter_range(*source, first..time_indices.len()).peekable()
+        })
+        .collect();
+    // Operands are x until their first change
+    let values = expression.operand_widths().iter().map(|w| logic::unknown(*w)).collect();
+    ExpressionStream { expression, from, cursors, values }
+}
+
+impl<'a, I: Iterator<Item = (TimeTableIdx, SignalValue<'a>)>> Iterator for ExpressionStream<'_, I> {
+    type Item = (TimeTableIdx, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let time_idx = self
+            .cursors
+            .iter_mut()
+            .filter_map(|cursor| cursor.peek().map(|(idx, _)| *idx))
+            .min()?
+            .max(self.from);
+        for (cursor, value) in self.cursors.iter_mut().zip(self.values.iter_mut()) {
+            while let Some((_, change)) = cursor.next_if(|(idx, _)| *idx <= time_idx) {
+                *value = four_state(&change, value.len());
+            }
+        }
+        Some((time_idx, self.expression.evaluate(&self.values)))
+    }
+}
+
+/// Four-state codes of a signal value, resized to an operand's width.
+pub(crate) fn four_state(value: &SignalValue, width: usize) -> Vec<u8> {
+    let states: Vec<u8> = bit_states(value).unwrap_or_default().into_iter().map(logic::to_four_state).collect();
+    logic::resize(&states, width)
+}
@@ -0,0 +1,229 @@
+//! The list view: every time at which any of a set of signals changes, with the values of
+//! all of them, read a page at a time in either direction or exported as CSV.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+use wellen::{SignalValue, Time, TimeTableIdx};
+
+use crate::changes::{first_change_at_or_after, value_at, SignalData};
+use crate::format::ValueFormat;
+use crate::navigation::NavigationDirection;
+
+#[derive(Serialize, Deserialize)]
+pub struct EventListQuery {
+    /// The columns of the list.
+    pub signal_ids: Vec<usize>,
+    /// Forward pages start at this time and backward pages end just before it. `None`
+    /// reads from the start of the trace forward, or from its end backward.
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    #[serde(default)]
+    pub direction: NavigationDirection,
+    pub limit: usize,
+    #[serde(default)]
+    pub format: Option<ValueFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EventRow {
+    pub(crate) time: u64,
+    /// Columns that change at this time.
+    pub(crate) changed: Vec<usize>,
+    /// Value of every column from this time on, `None` before a signal's first change.
+    pub(crate) values: Vec<Option<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventListPage {
+    /// In time order, whichever the direction.
+    pub(crate) rows: Vec<EventRow>,
+    /// Cursor of the page after this one, read forward. `None` at the end of the trace.
+    pub(crate) next_cursor: Option<u64>,
+    /// Cursor of the page before this one, read backward. `None` at the start of the trace.
+    pub(crate) previous_cursor: Option<u64>,
+}
+
+/// Times with a change, merged from the change lists of several signals.
+struct Step {
+    time_idx: TimeTableIdx,
+    changed: Vec<usize>,
+    /// Position of the change holding at the step in each signal's change list.
+    held: Vec<Option<usize>>,
+}
+
+/// K-way merge of the change lists of several signals, walking away from a cursor.
+struct Merge<'a> {
+    signals: &'a [SignalData<'a>],
+    direction: NavigationDirection,
+    /// Per signal, the position of the next change forward, or the one after it backward.
+    positions: Vec<usize>,
+    /// The next change of each signal, with the earliest forward, or the latest backward,
+    /// on top. Columns break ties, so that the lowest comes first.
+    heap: BinaryHeap<(i64, Reverse<usize>)>,
+}
+
+impl<'a> Merge<'a> {
+    fn new(signals: &'a [SignalData<'a>], time_table: &[Time], cursor: Option<Time>, direction: NavigationDirection) -> Self {
+        let positions = signals
+            .iter()
+            .map(|signal| match (cursor, direction) {
+                (Some(cursor), _) => first_change_at_or_after(signal.time_indices(), time_table, cursor),
+                (None, NavigationDirection::Forward) => 0,
+                (None, NavigationDirection::Backward) => signal.time_indices().len(),
+            })
+            .collect();
+        let mut merge = Merge { signals, direction, positions, heap: BinaryHeap::new() };
+        for column in 0..signals.len() {
+            merge.push(column);
+        }
+        merge
+    }
+
+    fn push(&mut self, column: usize) {
+        let position = match self.direction {
+            NavigationDirection::Forward => Some(self.positions[column]),
+            NavigationDirection::Backward => self.positions[column].checked_sub(1),
+        };
+        if let Some(time_idx) = position.and_then(|position| self.signals[column].time_indices().get(position)) {
+            let priority = match self.direction {
+                NavigationDirection::Forward => -(*time_idx as i64),
+                NavigationDirection::Backward => *time_idx as i64,
+            };
+            self.heap.push((priority, Reverse(column)));
+        }
+    }
+
+    fn peek(&self) -> Option<TimeTableIdx> {
+        self.heap.peek().map(|(priority, _)| priority.unsigned_abs() as TimeTableIdx)
+    }
+}
+
+impl Iterator for Merge<'_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let (priority, _) = *self.heap.peek()?;
+        let mut changed = Vec::new();
+        while let Some((_, Reverse(column))) = self.heap.peek().filter(|(p, _)| *p == priority).copied() {
+            self.heap.pop();
+            changed.push(column);
+        }
+
+        // Forward, the step's changes hold once taken; backward, until they are undone
+        let held_positions = |positions: &[usize]| positions.iter().map(|p| p.checked_sub(1)).collect();
+        let held = match self.direction {
+            NavigationDirection::Forward => {
+                changed.iter().for_each(|column| self.positions[*column] += 1);
+                held_positions(&self.positions)
+            }
+            NavigationDirection::Backward => {
+                let held = held_positions(&self.positions);
+                changed.iter().for_each(|column| self.positions[*column] -= 1);
+                held
+            }
+        };
+        for column in &changed {
+            self.push(*column);
+        }
+        Some(Step { time_idx: priority.unsigned_abs() as TimeTableIdx, changed, held })
+    }
+}
+
+fn render_row<R: Fn(&SignalValue) -> Result<String, String>>(
+    step: Step,
+    columns: &[(SignalData, R)],
+    time_table: &[Time],
+) -> Result<EventRow, String> {
+    let values = columns
+        .iter()
+        .zip(&step.held)
+        .map(|((signal, render), held)| held.and_then(|index| value_at(*signal, index)).map(|value| render(&value)).transpose())
+        .collect::<Result<_, String>>()?;
+    Ok(EventRow { time: time_table[step.time_idx as usize], changed: step.changed, values })
+}
+
+/// Reads one page of the list. `columns` pair each signal with the renderer of its values.
+pub(crate) fn page<R: Fn(&SignalValue) -> Result<String, String>>(
+    columns: &[(SignalData, R)],
+    time_table: &[Time],
+    query: &EventListQuery,
+) -> Result<EventListPage, String> {
+    let signals: Vec<SignalData> = columns.iter().map(|(signal, _)| *signal).collect();
+    let mut merge = Merge::new(&signals, time_table, query.cursor, query.direction);
+    let mut rows = merge
+        .by_ref()
+        .take(query.limit)
+        .map(|step| render_row(step, columns, time_table))
+        .collect::<Result<Vec<_>, String>>()?;
+    let beyond = merge.peek().map(|time_idx| time_table[time_idx as usize]);
+
+    // A cursor leads to a page when there is a change on its side of it
+    let has_page = |cursor: Time, direction| Merge::new(&signals, time_table, Some(cursor), direction).peek().is_some();
+    let (next_cursor, previous_cursor) = match query.direction {
+        NavigationDirection::Forward => {
+            let boundary = rows.first().map(|row| row.time).or(query.cursor);
+            (beyond, boundary.filter(|time| has_page(*time, NavigationDirection::Backward)))
+        }
+        NavigationDirection::Backward => {
+            rows.reverse();
+            let next = query.cursor.and_then(|cursor| {
+                Merge::new(&signals, time_table, Some(cursor), NavigationDirection::Forward)
+                    .peek()
+                    .map(|time_idx| time_table[time_idx as usize])
+            });
+            (next, beyond.and(rows.first().map(|row| row.time)))
+        }
+    };
+    Ok(EventListPage { rows, next_cursor, previous_cursor })
+}
+
+/// Reads every row of `[start, end]`.
+pub(crate) fn rows_between<R: Fn(&SignalValue) -> Result<String, String>>(
+    columns: &[(SignalData, R)],
+    time_table: &[Time],
+    start: Time,
+    end: Option<Time>,
+) -> Result<Vec<EventRow>, String> {
+    let signals: Vec<SignalData> = columns.iter().map(|(signal, _)| *signal).collect();
+    Merge::new(&signals, time_table, Some(start), NavigationDirection::Forward)
+        .take_while(|step| end.is_none_or(|end| time_table[step.time_idx as usize] <= end))
+        .map(|step| render_row(step, columns, time_table))
+        .collect()
+}
+
+/// Writes rows as CSV, under a header of `time` and the column names.
+pub(crate) fn to_csv(names: &[String], rows: &[EventRow]) -> String {
+    let mut out = String::new();
+    let header = std::iter::once("time").chain(names.iter().map(String::as_str)).map(csv_field);
+    out.push_str(&header.collect::<Vec<_>>().join(","));
+    out.push('\n');
+    for row in rows {
+        let values = row.values.iter().map(|value| csv_field(value.as_deref().unwrap_or_default()));
+        out.push_str(&std::iter::once(row.time.to_string()).chain(values).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes a field holding a comma, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("0101"), "0101");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,823 @@
+//! Verilog-like expressions over signals, evaluated with four-state semantics.
+//!
+//! Operands are hierarchical signal paths (`top.cpu.valid`, or `\escaped.name ` up to the next
+//! space) and literals (`8'hff`, `'b10x1`, `0x1f`, `12`). Unsized literals are as wide as
+//! their digits, or as the smallest width holding a decimal value. Supported, from
+//! tightest to loosest binding:
+//!
+//! - bit and part selects `a[3]`, `a[7:4]` (constant indices, using the declared range),
+//!   concatenation `{a, b}` and replication `{4{a}}`
+//! - unary `+ - ! ~` and reductions `& ~& | ~| ^ ~^`
+//! - `* / %`, then `+ -`, then `<< >>`
+//! - `< <= > >=`, then `== != === !==`
+//! - `&`, then `^ ~^`, then `|`, then `&&`, then `||`, then `?:`
+//!
+//! Assertions sample expressions at clock ticks and may also use `$rose(e)`, `$fell(e)`,
+//! `$stable(e)` and `$past(e)`, which compare against the previous tick.
+
+use crate::logic;
+use crate::value::{parse_literal, STATE_0, STATE_1};
+
+/// Widest literal, concatenation or replication accepted, in bits. Every value of such an
+/// expression is built in full at each change.
+const MAX_WIDTH: usize = 1 << 16;
+/// Deepest nesting of parentheses, operators and braces accepted. Parsing and evaluation
+/// recurse through it.
+const MAX_DEPTH: usize = 256;
+
+/// What a signal path refers to, as reported by the caller's resolver.
+pub(crate) struct ResolvedSignal {
+    pub(crate) signal_id: usize,
+    pub(crate) width: usize,
+    /// Declared `[msb:lsb]` range, used to map selects to bit positions.
+    pub(crate) index: Option<(i64, i64)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UnaryOp {
+    Plus,
+    Negate,
+    LogicalNot,
+    Not,
+    ReduceAnd,
+    ReduceNand,
+    ReduceOr,
+    ReduceNor,
+    ReduceXor,
+    ReduceXnor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Mul,
+    Div,
+    Rem,
+    Add,
+    Sub,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    CaseEqual,
+    CaseNotEqual,
+    And,
+    Xor,
+    Xnor,
+    Or,
+    LogicalAnd,
+    LogicalOr,
+}
+
+impl BinaryOp {
+    /// Operator and binding strength for a token, higher binds tighter.
+    fn from_token(token: &str) -> Option<(Self, u8)> {
+        Some(match token {
+            "*" => (BinaryOp::Mul, 10),
+            "/" => (BinaryOp::Div, 10),
+            "%" => (BinaryOp::Rem, 10),
+            "+" => (BinaryOp::Add, 9),
+            "-" => (BinaryOp::Sub, 9),
+            "<<" => (BinaryOp::ShiftLeft, 8),
+            ">>" => (BinaryOp::ShiftRight, 8),
+            "<" => (BinaryOp::Less, 7),
+            "<=" => (BinaryOp::LessEqual, 7),
+            ">" => (BinaryOp::Greater, 7),
+            ">=" => (BinaryOp::GreaterEqual, 7),
+            "==" => (BinaryOp::Equal, 6),
+            "!=" => (BinaryOp::NotEqual, 6),
+            "===" => (BinaryOp::CaseEqual, 6),
+            "!==" => (BinaryOp::CaseNotEqual, 6),
+            "&" => (BinaryOp::And, 5),
+            "^" => (BinaryOp::Xor, 4),
+            "~^" | "^~" => (BinaryOp::Xnor, 4),
+            "|" => (BinaryOp::Or, 3),
+            "&&" => (BinaryOp::LogicalAnd, 2),
+            "||" => (BinaryOp::LogicalOr, 1),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Literal(Vec<u8>),
+    /// Position in `Expression::operands`.
+    Operand(usize),
+    /// `width` states starting `from` states below the msb of the inner value.
+    Slice { inner: Box<Node>, from: usize, width: usize },
+    Concat(Vec<Node>),
+    Replicate(usize, Box<Node>),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Select(Box<Node>, Box<Node>, Box<Node>),
+    Sampled(SampledFunction, Box<Node>),
+}
+
+/// Functions over the value at the previous sample.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SampledFunction {
+    Rose,
+    Fell,
+    Stable,
+    Past,
+}
+
+/// A parsed expression with its signal references resolved.
+#[derive(Debug)]
+pub(crate) struct Expression {
+    root: Node,
+    /// Signal ids in order of first use.
+    operands: Vec<usize>,
+    operand_widths: Vec<usize>,
+}
+
+impl Expression {
+    pub(crate) fn parse(
+        text: &str,
+        resolve: impl Fn(&str, Option<(i64, i64)>) -> Result<ResolvedSignal, String>,
+    ) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            resolve,
+            operands: Vec::new(),
+            operand_widths: Vec::new(),
+            depth: 0,
+        };
+        let root = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{}' in expression", token.text()));
+        }
+        Ok(Expression {
+            root,
+            operands: parser.operands,
+            operand_widths: parser.operand_widths,
+        })
+    }
+
+    pub(crate) fn operands(&self) -> &[usize] {
+        &self.operands
+    }
+
+    pub(crate) fn operand_widths(&self) -> &[usize] {
+        &self.operand_widths
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        node_width(&self.root, &self.operand_widths)
+    }
+
+    /// Evaluates with the current four-state value of each operand.
+    pub(crate) fn evaluate(&self, operands: &[Vec<u8>]) -> Vec<u8> {
+        evaluate(&self.root, operands, operands)
+    }
+
+    /// Evaluates at a sample, with the operand values of the previous sample for
+    /// `$rose`, `$fell`, `$stable` and `$past`.
+    pub(crate) fn evaluate_sampled(&self, operands: &[Vec<u8>], previous: &[Vec<u8>]) -> Vec<u8> {
+        evaluate(&self.root, operands, previous)
+    }
+
+    /// Whether the expression uses `$rose`, `$fell`, `$stable` or `$past`, which only
+    /// make sense with a sampling clock.
+    pub(crate) fn is_sampled(&self) -> bool {
+        is_sampled(&self.root)
+    }
+}
+
+fn is_sampled(node: &Node) -> bool {
+    match node {
+        Node::Literal(_) | Node::Operand(_) => false,
+        Node::Slice { inner, .. } | Node::Replicate(_, inner) | Node::Unary(_, inner) => is_sampled(inner),
+        Node::Concat(parts) => parts.iter().any(is_sampled),
+        Node::Binary(_, left, right) => is_sampled(left) || is_sampled(right),
+        Node::Select(cond, then, otherwise) => is_sampled(cond) || is_sampled(then) || is_sampled(otherwise),
+        Node::Sampled(..) => true,
+    }
+}
+
+fn node_width(node: &Node, operand_widths: &[usize]) -> usize {
+    let width = |node: &Node| node_width(node, operand_widths);
+    match node {
+        Node::Literal(value) => value.len(),
+        Node::Operand(index) => operand_widths[*index],
+        Node::Slice { width, .. } => *width,
+        Node::Concat(parts) => parts.iter().map(width).sum(),
+        Node::Replicate(count, inner) => count * width(inner),
+        Node::Unary(UnaryOp::Plus | UnaryOp::Negate | UnaryOp::Not, inner) => width(inner),
+        Node::Unary(..) => 1,
+        Node::Binary(op, left, right) => match op {
+            BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Rem
+            | BinaryOp::Add
+            | BinaryOp::Sub
+            | BinaryOp::And
+            | BinaryOp::Xor
+            | BinaryOp::Xnor
+            | BinaryOp::Or => width(left).max(width(right)),
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight => width(left),
+            _ => 1,
+        },
+        Node::Select(_, then, otherwise) => width(then).max(width(otherwise)),
+        Node::Sampled(SampledFunction::Past, inner) => width(inner),
+        Node::Sampled(..) => 1,
+    }
+}
+
+fn node_height(node: &Node) -> usize {
+    1 + match node {
+        Node::Literal(_) | Node::Operand(_) => 0,
+        Node::Slice { inner, .. } | Node::Replicate(_, inner) | Node::Unary(_, inner) | Node::Sampled(_, inner) => node_height(inner),
+        Node::Concat(parts) => parts.iter().map(node_height).max().unwrap_or_default(),
+        Node::Binary(_, left, right) => node_height(left).max(node_height(right)),
+        Node::Select(cond, then, otherwise) => node_height(cond).max(node_height(then)).max(node_height(otherwise)),
+    }
+}
+
+fn evaluate(node: &Node, operands: &[Vec<u8>], previous: &[Vec<u8>]) -> Vec<u8> {
+    let evaluate = |node: &Node| evaluate(node, operands, previous);
+    match node {
+        Node::Literal(value) => value.clone(),
+        Node::Operand(index) => operands[*index].clone(),
+        Node::Slice { inner, from, width } => evaluate(inner)[*from..from + width].to_vec(),
+        Node::Concat(parts) => parts.iter().flat_map(&evaluate).collect(),
+        Node::Replicate(count, inner) => evaluate(inner).repeat(*count),
+        Node::Unary(op, inner) => {
+            let value = evaluate(inner);
+            match op {
+                UnaryOp::Plus => value,
+                UnaryOp::Negate => logic::negate(&value),
+                UnaryOp::LogicalNot => logic::logical_not(&value),
+                UnaryOp::Not => logic::not(&value),
+                UnaryOp::ReduceAnd => logic::reduce_and(&value),
+                UnaryOp::ReduceNand => logic::not(&logic::reduce_and(&value)),
+                UnaryOp::ReduceOr => logic::reduce_or(&value),
+                UnaryOp::ReduceNor => logic::not(&logic::reduce_or(&value)),
+                UnaryOp::ReduceXor => logic::reduce_xor(&value),
+                UnaryOp::ReduceXnor => logic::not(&logic::reduce_xor(&value)),
+            }
+        }
+        Node::Binary(op, left, right) => {
+            let (a, b) = (evaluate(left), evaluate(right));
+            match op {
+                BinaryOp::Mul => logic::mul(&a, &b),
+                BinaryOp::Div => logic::div(&a, &b),
+                BinaryOp::Rem => logic::rem(&a, &b),
+                BinaryOp::Add => logic::add(&a, &b),
+                BinaryOp::Sub => logic::sub(&a, &b),
+                BinaryOp::ShiftLeft => logic::shift(&a, &b, true),
+                BinaryOp::ShiftRight => logic::shift(&a, &b, false),
+                BinaryOp::Less => logic::less_than(&a, &b),
+                BinaryOp::LessEqual => logic::not(&logic::less_than(&b, &a)),
+                BinaryOp::Greater => logic::less_than(&b, &a),
+                BinaryOp::GreaterEqual => logic::not(&logic::less_than(&a, &b)),
+                BinaryOp::Equal => logic::equal(&a, &b),
+                BinaryOp::NotEqual => logic::not(&logic::equal(&a, &b)),
+                BinaryOp::CaseEqual => logic::case_equal(&a, &b),
+                BinaryOp::CaseNotEqual => logic::not(&logic::case_equal(&a, &b)),
+                BinaryOp::And => logic::and(&a, &b),
+                BinaryOp::Xor => logic::xor(&a, &b),
+                BinaryOp::Xnor => logic::not(&logic::xor(&a, &b)),
+                BinaryOp::Or => logic::or(&a, &b),
+                BinaryOp::LogicalAnd => logic::logical_and(&a, &b),
+                BinaryOp::LogicalOr => logic::logical_or(&a, &b),
+            }
+        }
+        Node::Select(cond, then, otherwise) => logic::select(&evaluate(cond), &evaluate(then), &evaluate(otherwise)),
+        Node::Sampled(function, inner) => {
+            // Nested functions see the previous sample as both current and previous
+            let before = self::evaluate(inner, previous, previous);
+            match function {
+                SampledFunction::Past => before,
+                SampledFunction::Stable => logic::case_equal(&evaluate(inner), &before),
+                SampledFunction::Rose | SampledFunction::Fell => {
+                    // Only the lsb counts, and a change from x or z counts as an edge
+                    let target = if *function == SampledFunction::Rose { STATE_1 } else { STATE_0 };
+                    logic::from_bool(evaluate(inner).last() == Some(&target) && before.last() != Some(&target))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Path(String),
+    Number(String),
+    /// `$rose` and friends.
+    Function(String),
+    Punct(&'static str),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Path(text) | Token::Number(text) | Token::Function(text) => text,
+            Token::Punct(text) => text,
+        }
+    }
+}
+
+/// Longest first, so that `===` is not read as `==` followed by `=`.
+const PUNCTUATION: [&str; 35] = [
+    "===", "!==", "&&", "||", "==", "!=", "<=", ">=", "<<", ">>", "~&", "~|", "~^", "^~", "(", ")", "[", "]", "{", "}",
+    ":", ",", "?", "~", "!", "&", "|", "^", "+", "-", "*", "/", "%", "<", ">",
+];
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let take_while = |i: &mut usize, accept: &dyn Fn(char) -> bool| {
+        let start = *i;
+        while *i < chars.len() && accept(chars[*i]) {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '\'' {
+            let mut literal = take_while(&mut i, &|c| c.is_ascii_digit() || c == '_');
+            if literal == "0" && i < chars.len() && "xXbBoO".contains(chars[i]) {
+                literal.push_str(&take_while(&mut i, &|c| c.is_ascii_alphanumeric() || c == '_'));
+            } else if i < chars.len() && chars[i] == '\'' {
+                i += 1;
+                literal.push('\'');
+                literal.push_str(&take_while(&mut i, &|c| c == 's' || c == 'S'));
+                if i < chars.len() {
+                    literal.push(chars[i]);
+                    i += 1;
+                }
+                literal.push_str(&take_while(&mut i, &|c| c.is_ascii_alphanumeric() || c == '_' || c == '?'));
+            }
+            tokens.push(Token::Number(literal));
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '\\' {
+            // Dotted paths are one token; escaped names run until whitespace
+            let mut path = String::new();
+            loop {
+                if i < chars.len() && chars[i] == '\\' {
+                    i += 1;
+                    path.push_str(&take_while(&mut i, &|c| !c.is_whitespace()));
+                    while i < chars.len() && chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                } else {
+                    path.push_str(&take_while(&mut i, &is_identifier_char));
+                }
+                if i + 1 < chars.len() && chars[i] == '.' && (is_identifier_char(chars[i + 1]) || chars[i + 1] == '\\') {
+                    path.push('.');
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Path(path));
+        } else if c == '$' && i + 1 < chars.len() && chars[i + 1].is_ascii_alphabetic() {
+            i += 1;
+            tokens.push(Token::Function(format!("${}", take_while(&mut i, &is_identifier_char))));
+        } else {
+            let rest: String = chars[i..].iter().take(3).collect();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|p| rest.starts_with(*p))
+                .ok_or_else(|| format!("Unexpected character '{}' in expression", c))?;
+            tokens.push(Token::Punct(punct));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Width of a literal token, before it is parsed into states.
+pub(crate) fn literal_width(text: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid literal: {}", text);
+    let capped = |width: usize| match width {
+        0..=MAX_WIDTH => Ok(width as u32),
+        _ => Err(format!("Literal {} is wider than {} bits", text, MAX_WIDTH)),
+    };
+    let digits_width = |digits: &str, digit_bits: usize| capped(digits.chars().filter(|c| *c != '_').count().saturating_mul(digit_bits));
+    let decimal_width = |digits: &str| -> Result<u32, String> {
+        let value: u128 = digits.replace('_', "").parse().map_err(|_| invalid())?;
+        Ok((128 - value.leading_zeros()).max(1))
+    };
+
+    if let Some((size, rest)) = text.split_once('\'') {
+        if !size.is_empty() {
+            let width: usize = size.replace('_', "").parse().ok().filter(|w| *w > 0).ok_or_else(invalid)?;
+            return capped(width);
+        }
+        let rest = rest.trim_start_matches(['s', 'S']);
+        let mut chars = rest.chars();
+        let base = chars.next().map(|c| c.to_ascii_lowercase());
+        let digits = chars.as_str();
+        return match base {
+            Some('b') => digits_width(digits, 1),
+            Some('o') => digits_width(digits, 3),
+            Some('h') => digits_width(digits, 4),
+            Some('d') => decimal_width(digits),
+            _ => Err(invalid()),
+        };
+    }
+    match text.get(..2).map(|prefix| prefix.to_ascii_lowercase()).as_deref() {
+        Some("0x") => digits_width(&text[2..], 4),
+        Some("0o") => digits_width(&text[2..], 3),
+        Some("0b") => digits_width(&text[2..], 1),
+        _ => decimal_width(text),
+    }
+}
+
+fn nested_too_deeply() -> String {
+    format!("Expression nested more than {} levels deep", MAX_DEPTH)
+}
+
+fn parse_number(text: &str) -> Result<Vec<u8>, String> {
+    let width = literal_width(text)?;
+    if width == 0 {
+        return Err(format!("Invalid literal: {}", text));
+    }
+    parse_literal(text, width)
+}
+
+struct Parser<R> {
+    tokens: Vec<Token>,
+    position: usize,
+    resolve: R,
+    operands: Vec<usize>,
+    operand_widths: Vec<usize>,
+    /// Parse calls in progress, bounded by `MAX_DEPTH`.
+    depth: usize,
+}
+
+impl<R: Fn(&str, Option<(i64, i64)>) -> Result<ResolvedSignal, String>> Parser<R> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(token) => Err(format!("Expected '{}' but found '{}'", punct, token.text())),
+                None => Err(format!("Expected '{}' at end of expression", punct)),
+            }
+        }
+    }
+
+    /// Runs a parse step one level deeper, failing past `MAX_DEPTH`.
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> Result<Node, String>) -> Result<Node, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(nested_too_deeply());
+        }
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn expression(&mut self) -> Result<Node, String> {
+        self.nested(Self::select)
+    }
+
+    /// `cond ? a : b` binds loosest and groups to the right.
+    fn select(&mut self) -> Result<Node, String> {
+        let cond = self.binary(1)?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.expression()?;
+        self.expect(":")?;
+        let otherwise = self.expression()?;
+        Ok(Node::Select(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+
+    /// Precedence climbing over the binary operators binding at least `min_strength`.
+    fn binary(&mut self, min_strength: u8) -> Result<Node, String> {
+        let mut left = self.unary()?;
+        let mut height = node_height(&left);
+        while let Some((op, strength)) = match self.peek() {
+            Some(Token::Punct(punct)) => BinaryOp::from_token(punct),
+            _ => None,
+        } {
+            if strength < min_strength {
+                break;
+            }
+            self.position += 1;
+            let right = self.binary(strength + 1)?;
+            // Chains such as `a + b + c` deepen the tree without nesting parse calls
+            height = height.max(node_height(&right)) + 1;
+            if height > MAX_DEPTH {
+                return Err(nested_too_deeply());
+            }
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        self.nested(Self::prefixed)
+    }
+
+    fn prefixed(&mut self) -> Result<Node, String> {
+        let op = match self.peek() {
+            Some(Token::Punct(punct)) => match *punct {
+                "+" => Some(UnaryOp::Plus),
+                "-" => Some(UnaryOp::Negate),
+                "!" => Some(UnaryOp::LogicalNot),
+                "~" => Some(UnaryOp::Not),
+                "&" => Some(UnaryOp::ReduceAnd),
+                "~&" => Some(UnaryOp::ReduceNand),
+                "|" => Some(UnaryOp::ReduceOr),
+                "~|" => Some(UnaryOp::ReduceNor),
+                "^" => Some(UnaryOp::ReduceXor),
+                "~^" | "^~" => Some(UnaryOp::ReduceXnor),
+                _ => None,
+            },
+            _ => None,
+        };
+        match op {
+            Some(op) => {
+                self.position += 1;
+                Ok(Node::Unary(op, Box::new(self.unary()?)))
+            }
+            None => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Node, String> {
+        let (mut node, mut index) = match self.next() {
+            Some(Token::Number(text)) => (Node::Literal(parse_number(&text)?), None),
+            Some(Token::Path(path)) => {
+                // `name[3]` may be a var of its own (split buses) rather than a bit select
+                if self.peek() == Some(&Token::Punct("[")) {
+                    let start = self.position;
+                    let range = self.constant_range()?;
+                    if let Ok(signal) = (self.resolve)(&path, Some(range)) {
+                        return Ok(self.operand(signal));
+                    }
+                    self.position = start;
+                }
+                let signal = (self.resolve)(&path, None)?;
+                let index = signal.index;
+                (self.operand(signal), index)
+            }
+            Some(Token::Punct("(")) => {
+                let inner = self.expression()?;
+                self.expect(")")?;
+                (inner, None)
+            }
+            Some(Token::Punct("{")) => (self.concatenation()?, None),
+            Some(Token::Function(name)) => {
+                let function = match name.as_str() {
+                    "$rose" => SampledFunction::Rose,
+                    "$fell" => SampledFunction::Fell,
+                    "$stable" => SampledFunction::Stable,
+                    "$past" => SampledFunction::Past,
+                    _ => return Err(format!("Unknown function {}", name)),
+                };
+                self.expect("(")?;
+                let inner = self.expression()?;
+                self.expect(")")?;
+                (Node::Sampled(function, Box::new(inner)), None)
+            }
+            Some(token) => return Err(format!("Unexpected '{}' in expression", token.text())),
+            None => return Err("Unexpected end of expression".to_string()),
+        };
+
+        while self.peek() == Some(&Token::Punct("[")) {
+            let (msb, lsb) = self.constant_range()?;
+            let width = node_width(&node, &self.operand_widths);
+            // Selects count from the declared msb, or from `width - 1` for plain values
+            let (declared_msb, declared_lsb) = index.unwrap_or((width as i64 - 1, 0));
+            let position = |bit: i64| {
+                let offset = if declared_msb >= declared_lsb { declared_msb - bit } else { bit - declared_msb };
+                usize::try_from(offset)
+                    .ok()
+                    .filter(|offset| *offset < width)
+                    .ok_or_else(|| format!("Index {} is outside [{}:{}]", bit, declared_msb, declared_lsb))
+            };
+            let (from, to) = (position(msb)?, position(lsb)?);
+            if from > to {
+                return Err(format!("Part select [{}:{}] is reversed", msb, lsb));
+            }
+            node = Node::Slice { inner: Box::new(node), from, width: to - from + 1 };
+            index = None;
+        }
+        Ok(node)
+    }
+
+    fn concatenation(&mut self) -> Result<Node, String> {
+        self.nested(Self::braced)
+    }
+
+    /// `{a, b, ...}` or `{n{a, ...}}`, after the opening brace.
+    fn braced(&mut self) -> Result<Node, String> {
+        let first = self.expression()?;
+        if self.eat("{") {
+            let count = match &first {
+                Node::Literal(value) => logic::to_usize(value).filter(|count| *count > 0),
+                _ => None,
+            }
+            .ok_or("Replication count must be a positive constant")?;
+            let inner = self.concatenation()?;
+            self.expect("}")?;
+            self.check_width(count.checked_mul(node_width(&inner, &self.operand_widths)))?;
+            return Ok(Node::Replicate(count, Box::new(inner)));
+        }
+        let mut parts = vec![first];
+        while self.eat(",") {
+            parts.push(self.expression()?);
+        }
+        self.expect("}")?;
+        let width = parts.iter().try_fold(0usize, |total, part| total.checked_add(node_width(part, &self.operand_widths)));
+        self.check_width(width)?;
+        Ok(if parts.len() == 1 { parts.pop().unwrap() } else { Node::Concat(parts) })
+    }
+
+    /// Rejects the width of a concatenation or replication, `None` when it overflowed.
+    fn check_width(&self, width: Option<usize>) -> Result<(), String> {
+        match width {
+            Some(width) if width <= MAX_WIDTH => Ok(()),
+            _ => Err(format!("Expression is wider than {} bits", MAX_WIDTH)),
+        }
+    }
+
+    /// `[msb:lsb]` or `[bit]` with integer indices.
+    fn constant_range(&mut self) -> Result<(i64, i64), String> {
+        self.expect("[")?;
+        let msb = self.integer()?;
+        let lsb = if self.eat(":") { self.integer()? } else { msb };
+        self.expect("]")?;
+        Ok((msb, lsb))
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        let negative = self.eat("-");
+        match self.next() {
+            Some(Token::Number(text)) if text.chars().all(|c| c.is_ascii_digit() || c == '_') => {
+                let value: i64 = text.replace('_', "").parse().map_err(|_| format!("Invalid index: {}", text))?;
+                Ok(if negative { -value } else { value })
+            }
+            Some(token) => Err(format!("Index must be an integer, found '{}'", token.text())),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+
+    fn operand(&mut self, signal: ResolvedSignal) -> Node {
+        let index = match self.operands.iter().position(|id| *id == signal.signal_id) {
+            Some(index) => index,
+            None => {
+                self.operands.push(signal.signal_id);
+                self.operand_widths.push(signal.width);
+                self.operands.len() - 1
+            }
+        };
+        Node::Operand(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::STATE_X;
+
+    /// Signals `a` (4 bits, declared [3:0]), `b` (4 bits) and `bus` (declared [8:15]).
+    fn resolve(path: &str, index: Option<(i64, i64)>) -> Result<ResolvedSignal, String> {
+        let (signal_id, width, declared) = match path {
+            "top.a" if index.is_none() => (0, 4, Some((3, 0))),
+            "top.b" if index.is_none() => (1, 4, None),
+            "top.bus" if index.is_none() => (2, 8, Some((8, 15))),
+            _ => return Err(format!("Unknown signal: {}", path)),
+        };
+        Ok(ResolvedSignal { signal_id, width, index: declared })
+    }
+
+    fn eval(text: &str, a: &str, b: &str) -> String {
+        let expression = Expression::parse(text, resolve).unwrap();
+        let states = |text: &str| text.chars().map(|c| "01xz".find(c).unwrap() as u8).collect::<Vec<u8>>();
+        let bus = states("10000001");
+        let values: Vec<Vec<u8>> = expression
+            .operands()
+            .iter()
+            .map(|id| match id {
+                0 => states(a),
+                1 => states(b),
+                _ => bus.clone(),
+            })
+            .collect();
+        let result = expression.evaluate(&values);
+        assert_eq!(result.len(), expression.width(), "width of {}", text);
+        result.iter().map(|s| ['0', '1', 'x', 'z'][*s as usize]).collect()
+    }
+
+    #[test]
+    fn test_operators_and_precedence() {
+        assert_eq!(eval("top.a & top.b", "1100", "1010"), "1000");
+        assert_eq!(eval("top.a ^ top.b | 4'b0001", "1100", "1010"), "0111");
+        assert_eq!(eval("top.a + top.b * 2", "0001", "0011"), "0111");
+        assert_eq!(eval("(top.a + top.b) * 2", "0001", "0011"), "1000");
+        assert_eq!(eval("top.a == 4'hc && top.b != 0", "1100", "1010"), "1");
+        assert_eq!(eval("top.a < top.b ? top.a : top.b", "0011", "0101"), "0011");
+        assert_eq!(eval("top.a >= top.b", "0011", "0101"), "0");
+        assert_eq!(eval("~top.a", "1x00", "0000"), "0x11");
+        assert_eq!(eval("&top.a | ^top.b", "1111", "0000"), "1");
+        assert_eq!(eval("!top.a", "0x00", "0000"), "x");
+        assert_eq!(eval("top.a << 1", "0111", "0000"), "1110");
+        assert_eq!(eval("top.a === 4'b1x00", "1x00", "0000"), "1");
+        assert_eq!(eval("-top.a", "0001", "0000"), "1111");
+    }
+
+    #[test]
+    fn test_selects_and_concatenation() {
+        assert_eq!(eval("top.a[3:2]", "1100", "0000"), "11");
+        assert_eq!(eval("top.a[0]", "1101", "0000"), "1");
+        assert_eq!(eval("{top.a[1:0], top.b}", "1101", "0110"), "010110");
+        assert_eq!(eval("{2{top.a[3:2]}}", "1001", "0000"), "1010");
+        assert_eq!(eval("{1'b0, top.a} + top.b", "1111", "0001"), "10000");
+        // Ascending declared ranges count from the left
+        assert_eq!(eval("top.bus[8]", "0000", "0000"), "1");
+        assert_eq!(eval("top.bus[8:10]", "0000", "0000"), "100");
+        assert_eq!(eval("(top.a + top.b)[3:2]", "0001", "0011"), "01");
+    }
+
+    #[test]
+    fn test_literal_widths() {
+        assert_eq!(parse_number("8'hff").unwrap().len(), 8);
+        assert_eq!(parse_number("'hff").unwrap().len(), 8);
+        assert_eq!(parse_number("0x1f").unwrap().len(), 8);
+        assert_eq!(parse_number("5").unwrap(), vec![1, 0, 1]);
+        assert_eq!(parse_number("0").unwrap(), vec![0]);
+        assert_eq!(parse_number("4'bx").unwrap(), vec![STATE_X; 4]);
+    }
+
+    #[test]
+    fn test_sampled_functions() {
+        let sampled = |text: &str, now: &str, before: &str| {
+            let expression = Expression::parse(text, resolve).unwrap();
+            assert!(expression.is_sampled());
+            let states = |text: &str| vec![text.chars().map(|c| "01xz".find(c).unwrap() as u8).collect::<Vec<u8>>()];
+            let result = expression.evaluate_sampled(&states(now), &states(before));
+            result.iter().map(|s| ['0', '1', 'x', 'z'][*s as usize]).collect::<String>()
+        };
+        assert_eq!(sampled("$rose(top.a)", "0001", "0000"), "1");
+        assert_eq!(sampled("$rose(top.a)", "0001", "xxxx"), "1");
+        assert_eq!(sampled("$rose(top.a)", "0001", "1111"), "0");
+        assert_eq!(sampled("$fell(top.a[0])", "0000", "0001"), "1");
+        assert_eq!(sampled("$stable(top.a)", "01x0", "01x0"), "1");
+        assert_eq!(sampled("$past(top.a) + 1", "0000", "0011"), "0100");
+        assert!(!Expression::parse("top.a + 1", resolve).unwrap().is_sampled());
+        assert_eq!(Expression::parse("$changed(top.a)", resolve).unwrap_err(), "Unknown function $changed");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text: &str| Expression::parse(text, resolve).unwrap_err();
+        assert_eq!(error("top.c"), "Unknown signal: top.c");
+        assert_eq!(error("top.a &"), "Unexpected end of expression");
+        assert_eq!(error("(top.a"), "Expected ')' at end of expression");
+        assert_eq!(error("top.a[4]"), "Index 4 is outside [3:0]");
+        assert_eq!(error("top.a[0:3]"), "Part select [0:3] is reversed");
+        assert_eq!(error("{top.b{top.a}}"), "Replication count must be a positive constant");
+        assert_eq!(error("{64'hFFFFFFFFFFFFFFFF{top.a}}"), "Expression is wider than 65536 bits");
+        assert_eq!(error("{100000000{top.a}}"), "Expression is wider than 65536 bits");
+        assert_eq!(error("{{16384{top.a}}, top.b}"), "Expression is wider than 65536 bits");
+        assert_eq!(error("1000000000'h0"), "Literal 1000000000'h0 is wider than 65536 bits");
+        let deep = "Expression nested more than 256 levels deep";
+        assert_eq!(error(&format!("{}top.a{}", "(".repeat(100_000), ")".repeat(100_000))), deep);
+        assert_eq!(error(&format!("{}top.a", "~".repeat(100_000))), deep);
+        assert_eq!(error(&format!("top.a{}", " + top.a".repeat(100_000))), deep);
+        assert_eq!(error(&format!("{}top.a{}", "{2{".repeat(100_000), "}}".repeat(100_000))), deep);
+        assert!(Expression::parse(&format!("{}top.a{}", "(".repeat(50), ")".repeat(50)), resolve).is_ok());
+        assert_eq!(error("top.a # 1"), "Unexpected character '#' in expression");
+        assert_eq!(error("top.a top.b"), "Unexpected 'top.b' in expression");
+    }
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use wellen::SignalValue;
+
+use crate::value::{bit_states, state_to_bit, STATE_Z};
+
+/// How bit-vector values are rendered. Real and string values are always shown as-is.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValueFormat {
+    #[default]
+    Binary,
+    Hex,
+    Octal,
+    Unsigned,
+    Signed,
+    Ascii,
+    Float16,
+    Float32,
+    Float64,
+    /// Qm.n fixed point: `frac_bits` is n, the remaining bits hold the integer part.
+    Fixed {
+        frac_bits: u32,
+        #[serde(default)]
+        signed: bool,
+    },
+}
+
+pub fn format_value(value: &SignalValue, format: ValueFormat) -> String {
+    let states = match (format, bit_states(value)) {
+        (ValueFormat::Binary, _) | (_, None) => return value.to_string(),
+        (_, Some(states)) => states,
+    };
+    match format {
+        ValueFormat::Binary => unreachable!(),
+        ValueFormat::Hex => format_radix(&states, 4),
+        ValueFormat::Octal => format_radix(&states, 3),
+        ValueFormat::Unsigned => with_known_bits(&states, to_decimal),
+        ValueFormat::Signed => with_known_bits(&states, |bits| {
+            if bits.first() == Some(&true) {
+                format!("-{}", to_decimal(&twos_complement(bits)))
+            } else {
+                to_decimal(bits)
+            }
+        }),
+        ValueFormat::Ascii => format_ascii(&states),
+        ValueFormat::Float16 => with_known_bits(&states, |bits| f16_to_f64(low_bits(bits, 16) as u16).to_string()),
+        ValueFormat::Float32 => with_known_bits(&states, |bits| f32::from_bits(low_bits(bits, 32) as u32).to_string()),
+        ValueFormat::Float64 => with_known_bits(&states, |bits| f64::from_bits(low_bits(bits, 64)).to_string()),
+        ValueFormat::Fixed { frac_bits, signed } => with_known_bits(&states, |bits| {
+            let mut value = bits.iter().fold(0.0, |acc, bit| acc * 2.0 + if *bit { 1.0 } else { 0.0 });
+            if signed && bits.first() == Some(&true) {
+                value -= 2f64.powi(bits.len() as i32);
+            }
+            (value / 2f64.powi(frac_bits as i32)).to_string()
+        }),
+    }
+}
+
+/// Numeric formats have no digit to show for a partly unknown value: the whole value
+/// becomes `z` when every bit floats and `x` otherwise.
+fn with_known_bits(states: &[u8], render: impl FnOnce(&[bool]) -> String) -> String {
+    match states.iter().map(|s| state_to_bit(*s)).collect::<Option<Vec<bool>>>() {
+        Some(bits) => render(&bits),
+        None if states.iter().all(|s| *s == STATE_Z) => "z".to_string(),
+        None => "x".to_string(),
+    }
+}
+
+/// Renders groups of `group_bits` bits, starting from the lsb. A digit whose bits are all
+/// unknown shows as `x` (or `z` when they all float); a digit mixing known and unknown
+/// bits shows as `X` (or `Z`).
+fn format_radix(states: &[u8], group_bits: usize) -> String {
+    let mut digits = Vec::with_capacity(states.len().div_ceil(group_bits));
+    for group in states.rchunks(group_bits) {
+        let bits: Vec<Option<bool>> = group.iter().map(|s| state_to_bit(*s)).collect();
+        let unknown = bits.iter().filter(|b| b.is_none()).count();
+        let all_z = group.iter().filter(|s| state_to_bit(**s).is_none()).all(|s| *s == STATE_Z);
+        let digit = match (unknown, all_z) {
+            (0, _) => {
+                let value = bits.iter().fold(0, |acc, b| acc * 2 + b.unwrap() as u32);
+                char::from_digit(value, 16).unwrap()
+            }
+            (n, true) if n == group.len() => 'z',
+            (n, false) if n == group.len() => 'x',
+            (_, true) => 'Z',
+            (_, false) => 'X',
+        };
+        digits.push(digit);
+    }
+    digits.iter().rev().collect()
+}
+
+/// One character per byte, starting from the msb. Unprintable bytes show as `.` and
+/// bytes with unknown bits as `?`; leading NUL bytes (padding) are dropped.
+fn format_ascii(states: &[u8]) -> String {
+    let mut bytes: Vec<&[u8]> = states.rchunks(8).collect();
+    bytes.reverse();
+    bytes
+        .iter()
+        .map(|byte| {
+            byte.iter()
+                .map(|s| state_to_bit(*s))
+                .try_fold(0u8, |acc, b| Some(acc << 1 | b? as u8))
+        })
+        .skip_while(|byte| *byte == Some(0))
+        .map(|byte| match byte {
+            Some(b) if b.is_ascii_graphic() || b == b' ' => b as char,
+            Some(_) => '.',
+            None => '?',
+        })
+        .collect()
+}
+
+/// Value of the lowest `count` bits (at most 64) of an msb-first bit vector.
+fn low_bits(bits: &[bool], count: usize) -> u64 {
+    bits[bits.len().saturating_sub(count)..]
+        .iter()
+        .fold(0, |acc, bit| acc << 1 | *bit as u64)
+}
+
+fn twos_complement(bits: &[bool]) -> Vec<bool> {
+    let mut out: Vec<bool> = bits.iter().map(|b| !b).collect();
+    for bit in out.iter_mut().rev() {
+        *bit = !*bit;
+        if *bit {
+            break;
+        }
+    }
+    out
+}
+
+/// Decimal rendering of an unsigned msb-first bit vector of any width.
+fn to_decimal(bits: &[bool]) -> String {
+    // Little-endian base 2^32 limbs
+    let mut limbs: Vec<u32> = bits
+        .rchunks(32)
+        .map(|chunk| chunk.iter().fold(0u32, |acc, bit| acc << 1 | *bit as u32))
+        .collect();
+
+    let mut chunks = Vec::new();
+    loop {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        if limbs.is_empty() {
+            break;
+        }
+        let mut remainder = 0u64;
+        for limb in limbs.iter_mut().rev() {
+            let current = (remainder << 32) | *limb as u64;
+            *limb = (current / 1_000_000_000) as u32;
+            remainder = current % 1_000_000_000;
+        }
+        chunks.push(remainder as u32);
+    }
+
+    match chunks.split_last() {
+        None => "0".to_string(),
+        Some((most_significant, rest)) => {
+            let mut out = most_significant.to_string();
+            for chunk in rest.iter().rev() {
+                out.push_str(&format!("{:09}", chunk));
+            }
+            out
+        }
+    }
+}
+
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits >> 15 == 1 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    match exponent {
+        0 => sign * mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a nine-state value from a bit string; wellen packs two states per byte.
+    fn format_bits(bits: &str, format: ValueFormat) -> String {
+        let codes: Vec<u8> = bits
+            .chars()
+            .map(|c| "01xzhuwl-".find(c).unwrap() as u8)
+            .collect();
+        let mut data = Vec::new();
+        for pair in codes.rchunks(2) {
+            data.push(pair.iter().fold(0, |acc, code| acc << 4 | code));
+        }
+        data.reverse();
+        format_value(&SignalValue::NineValue(&data, codes.len() as u32), format)
+    }
+
+    #[test]
+    fn test_radix_formats_with_unknown_digits() {
+        assert_eq!(format_bits("10101111", ValueFormat::Hex), "af");
+        assert_eq!(format_bits("110101111", ValueFormat::Hex), "1af");
+        assert_eq!(format_bits("xxxx0001", ValueFormat::Hex), "x1");
+        assert_eq!(format_bits("x0010001", ValueFormat::Hex), "X1");
+        assert_eq!(format_bits("zzzzz001", ValueFormat::Hex), "zZ");
+        assert_eq!(format_bits("111101", ValueFormat::Octal), "75");
+        assert_eq!(format_bits("1x1101", ValueFormat::Octal), "X5");
+        assert_eq!(format_bits("1h0l", ValueFormat::Hex), "c");
+    }
+
+    #[test]
+    fn test_decimal_formats() {
+        assert_eq!(format_bits("11111111", ValueFormat::Unsigned), "255");
+        assert_eq!(format_bits("11111111", ValueFormat::Signed), "-1");
+        assert_eq!(format_bits("10000000", ValueFormat::Signed), "-128");
+        assert_eq!(format_bits("01111111", ValueFormat::Signed), "127");
+        assert_eq!(format_bits("0000", ValueFormat::Unsigned), "0");
+        assert_eq!(format_bits("1x11", ValueFormat::Unsigned), "x");
+        assert_eq!(format_bits("zzzz", ValueFormat::Signed), "z");
+
+        // 2^100 needs more than a u64
+        let wide = format!("1{}", "0".repeat(100));
+        assert_eq!(format_bits(&wide, ValueFormat::Unsigned), "1267650600228229401496703205376");
+        let minus_one = "1".repeat(100);
+        assert_eq!(format_bits(&minus_one, ValueFormat::Signed), "-1");
+    }
+
+    #[test]
+    fn test_ascii_float_and_fixed_formats() {
+        assert_eq!(format_bits("000000000100100001101001", ValueFormat::Ascii), "Hi");
+        assert_eq!(format_bits("0100100000000001", ValueFormat::Ascii), "H.");
+        assert_eq!(format_bits("01001000xxxxxxxx", ValueFormat::Ascii), "H?");
+
+        assert_eq!(format_bits("0011110000000000", ValueFormat::Float16), "1");
+        assert_eq!(format_bits("1100000000000000", ValueFormat::Float16), "-2");
+        let one_and_half = format!("{:032b}", 1.5f32.to_bits());
+        assert_eq!(format_bits(&one_and_half, ValueFormat::Float32), "1.5");
+        let pi = format!("{:064b}", std::f64::consts::PI.to_bits());
+        assert_eq!(format_bits(&pi, ValueFormat::Float64), std::f64::consts::PI.to_string());
+
+        let q4_4 = ValueFormat::Fixed { frac_bits: 4, signed: true };
+        assert_eq!(format_bits("00011000", q4_4), "1.5");
+        assert_eq!(format_bits("11111000", q4_4), "-0.5");
+        let uq4_4 = ValueFormat::Fixed { frac_bits: 4, signed: false };
+        assert_eq!(format_bits("11111000", uq4_4), "15.5");
+    }
+
+    #[test]
+    fn test_non_vector_values_pass_through() {
+        assert_eq!(format_value(&SignalValue::Real(2.5), ValueFormat::Hex), "2.5");
+        assert_eq!(format_value(&SignalValue::String("idle"), ValueFormat::Signed), "idle");
+    }
+}
@@ -1,8 +1,49 @@
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 use wasm_bindgen::prelude::*;
-use wellen;
 use serde::{Serialize, Deserialize};
 
+mod activity;
+mod analog;
+mod assertion;
+mod bus;
+mod changes;
+mod clock;
+mod cycles;
+mod evaluate;
+mod event_list;
+mod expression;
+mod format;
+mod logic;
+mod navigation;
+mod nearby;
+mod packed;
+mod sampling;
+mod search;
+mod statistics;
+mod summary;
+mod temporal;
+mod translation;
+mod value;
+mod virtual_signal;
+
+pub use activity::{ActivityMap, ActivitySource};
+pub use analog::{AnalogSample, AnalogWindow, Interpolation};
+pub use assertion::{AssertionAttempt, AssertionReport, AttemptStatus};
+pub use bus::BusField;
+pub use clock::{ClockInfo, GatedInterval};
+pub use cycles::{ClockEdge, CycleAxisInfo};
+pub use event_list::{EventListPage, EventListQuery, EventRow};
+pub use format::ValueFormat;
+pub use navigation::{NavigationDirection, NavigationTarget};
+pub use nearby::{NearbyChange, NearbyChanges};
+pub use sampling::{SampleQuery, SampledRow, SampledTable};
+pub use search::{SearchMatch, SearchMode, SearchQuery, SearchResults};
+pub use statistics::{SignalStatistics, StableInterval};
+pub use summary::SummaryBucket;
+pub use temporal::{TemporalCondition, TemporalMatch, TemporalQuery, TemporalSearchResults};
+pub use translation::{TranslationFormat, ValueTranslator};
+
 // We need a thread-safe global state for the Tauri side (multi-threaded).
 // For Wasm (single-threaded usually), Mutex is still fine or we could use RefCell/thread_local.
 // Since we want to share code, we'll use a static Mutex.
@@ -14,6 +55,16 @@ pub struct SignalChange {
     value: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct ValueAt {
+    /// `None` before the signal's first change.
+    value: Option<String>,
+    /// Time of the change that set `value`, at or before the queried time.
+    previous_change: Option<u64>,
+    /// Time of the first change after the queried time.
+    next_change: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HierarchyScope {
     name: String,
@@ -28,6 +79,47 @@ pub struct HierarchyVar {
     name: String,
     #[serde(rename = "ref")]
     ref_: usize,
+    /// Number of bits, `None` for real and string vars.
+    width: Option<u32>,
+    var_type: String,
+    direction: String,
+    index: Option<VarIndexRange>,
+    encoding: String,
+    /// Source expression of a virtual signal, absent for vars from the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expression: Option<String>,
+    /// Name of the translation table that labels the values, if one is attached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+    /// True when another var declared earlier refers to the same signal.
+    is_alias: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VarIndexRange {
+    msb: i64,
+    lsb: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScopeSummary {
+    name: String,
+    #[serde(rename = "ref")]
+    ref_: usize,
+    scope_type: String,
+    scope_count: usize,
+    var_count: usize,
+    has_children: bool,
+}
+
+/// One page of the direct children of a scope. Child scopes come first, then vars;
+/// `offset` and `limit` apply to that combined list.
+#[derive(Serialize, Deserialize)]
+pub struct ScopeChildren {
+    scopes: Vec<ScopeSummary>,
+    vars: Vec<HierarchyVar>,
+    total_scopes: usize,
+    total_vars: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -39,19 +131,151 @@ pub struct HierarchyRoot {
     scopes: Vec<HierarchyScope>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct TimescaleInfo {
+    factor: u32,
+    unit: String,
+    /// Power of ten of the unit in seconds (e.g. -9 for ns), if known.
+    exponent: Option<i8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WaveformInfo {
+    timescale: Option<TimescaleInfo>,
+    /// First and last entries of the time table, in timescale units.
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    file_format: String,
+    date: String,
+    version: String,
+    var_count: usize,
+    scope_count: usize,
+    signal_count: usize,
+}
+
 pub struct LoadedWave {
     pub path: String,
     pub wave: wellen::simple::Waveform,
+    /// First var declared for each signal, used to tell aliases apart.
+    canonical_vars: Vec<Option<wellen::VarRef>>,
+    search_index: search::SearchIndex,
+    /// Built on the first summary request for a numeric or real signal, keyed by signal id.
+    min_max_pyramids: HashMap<usize, summary::MinMaxPyramid>,
+    /// Signals computed from expressions, starting with the buses grouped from single-bit
+    /// vars. Their ids follow the file's own signals; removed ones leave a `None` so that
+    /// the ids of the others stay valid.
+    virtual_signals: Vec<Option<virtual_signal::VirtualSignal>>,
+    /// Value-to-label tables by name, including one per enum type of the file.
+    translation_tables: BTreeMap<String, translation::TranslationTable>,
+    /// Name of the table attached to each signal that has one.
+    signal_translations: HashMap<usize, String>,
+    /// Translators attached to signals, locked while they render values.
+    signal_translators: HashMap<usize, SharedTranslator>,
+    /// How analog views draw each signal that does not use the default.
+    interpolations: HashMap<usize, Interpolation>,
+    /// Clock analysis of the signals analysed so far, `None` for those that are no clock.
+    clocks: HashMap<usize, Option<clock::ClockInfo>>,
+    /// Cycle axes built so far, by clock signal and edge.
+    cycle_axes: HashMap<(usize, ClockEdge), cycles::CycleAxis>,
+    /// Latest activity heatmap of each source. A request for another number of buckets
+    /// replaces it, so that resizing the minimap does not pile up heatmaps.
+    activity_maps: HashMap<ActivitySource, ActivityMap>,
 }
 
 static OPENED_FILES: Mutex<Vec<LoadedWave>> = Mutex::new(Vec::new());
 
+/// A translator can be used once `OPENED_FILES` is released, as it may take a while to answer.
+type SharedTranslator = Arc<Mutex<Box<dyn ValueTranslator>>>;
+
+/// Ids of the temporal searches running, each with whether it was asked to stop. Kept
+/// apart from `OPENED_FILES`, which a running search holds.
+static ACTIVE_SEARCHES: Mutex<Vec<(u32, bool)>> = Mutex::new(Vec::new());
+
+/// Registers a search in `ACTIVE_SEARCHES` until dropped.
+struct ActiveSearch(Option<u32>);
+
+impl ActiveSearch {
+    fn start(search_id: Option<u32>) -> Self {
+        if let Some(id) = search_id {
+            ACTIVE_SEARCHES.lock().unwrap().push((id, false));
+        }
+        ActiveSearch(search_id)
+    }
+
+    fn cancelled(&self) -> bool {
+        self.0.is_some_and(|id| ACTIVE_SEARCHES.lock().unwrap().iter().any(|(active, cancelled)| *active == id && *cancelled))
+    }
+}
+
+impl Drop for ActiveSearch {
+    fn drop(&mut self) {
+        if let Some(id) = self.0 {
+            let mut active = ACTIVE_SEARCHES.lock().unwrap();
+            if let Some(position) = active.iter().position(|(active, _)| *active == id) {
+                active.remove(position);
+            }
+        }
+    }
+}
+
 pub fn add_file(path: String, waveform: wellen::simple::Waveform) {
-    let mut files = OPENED_FILES.lock().unwrap();
+    // Everything is built before taking the lock, which would block the queries on the
+    // other files while scanning a large hierarchy
+    let hierarchy = waveform.hierarchy();
+    let mut canonical_vars = vec![None; hierarchy.num_unique_signals()];
+    for (index, var) in hierarchy.iter_vars().enumerate() {
+        let owner = &mut canonical_vars[var.signal_ref().index()];
+        if owner.is_none() {
+            *owner = wellen::VarRef::from_index(index);
+        }
+    }
+
+    let search_index = search::SearchIndex::build(hierarchy);
+
+    // Buses dumped bit by bit are offered as one var, evaluated when first queried
+    let virtual_signals = bus::find_bit_groups(hierarchy)
+        .into_iter()
+        .map(|group| {
+            let parts: Vec<String> = group.bits.iter().map(|bit| bus::var_path(hierarchy, *bit)).collect();
+            let first_bit = &hierarchy[group.bits[0]];
+            let mut signal = virtual_signal::VirtualSignal::new(group.name, bus::concatenation(&parts), group.bits.len());
+            signal.scope = group.scope;
+            signal.index = Some((group.msb, group.lsb));
+            signal.var_type = var_type_name(first_bit.var_type());
+            signal.direction = direction_name(first_bit.direction());
+            Some(signal)
+        })
+        .collect();
+
+    // Enum literals, as GHW files declare them for VHDL enum types, label their signals
+    let mut translation_tables = BTreeMap::new();
+    let mut signal_translations = HashMap::new();
+    for var in hierarchy.iter_vars() {
+        if let Some((name, literals)) = var.enum_type(hierarchy) {
+            if !translation_tables.contains_key(name) {
+                match translation::TranslationTable::from_enum(&literals) {
+                    Ok(table) => translation_tables.insert(name.to_string(), table),
+                    Err(_) => continue,
+                };
+            }
+            signal_translations.insert(var.signal_ref().index(), name.to_string());
+        }
+    }
 
-    files.push(LoadedWave {
+    OPENED_FILES.lock().unwrap().push(LoadedWave {
         path,
         wave: waveform,
+        canonical_vars,
+        search_index,
+        min_max_pyramids: HashMap::new(),
+        virtual_signals,
+        translation_tables,
+        signal_translations,
+        signal_translators: HashMap::new(),
+        interpolations: HashMap::new(),
+        clocks: HashMap::new(),
+        cycle_axes: HashMap::new(),
+        activity_maps: HashMap::new(),
     });
 }
 
@@ -69,6 +293,16 @@ pub fn remove_file(path: String) {
     }
 }
 
+fn find_file<'a>(files: &'a [LoadedWave], filename: &str) -> Result<&'a LoadedWave, String> {
+    files.iter().find(|f| f.path.ends_with(filename) || f.path == filename)
+        .ok_or_else(|| format!("File not found: {}", filename))
+}
+
+fn find_file_mut<'a>(files: &'a mut [LoadedWave], filename: &str) -> Result<&'a mut LoadedWave, String> {
+    files.iter_mut().find(|f| f.path.ends_with(filename) || f.path == filename)
+        .ok_or_else(|| format!("File not found: {}", filename))
+}
+
 #[wasm_bindgen]
 pub fn add_file_bytes(name: String, content: Vec<u8>) -> Result<String, String> {
     let cursor = std::io::Cursor::new(content);
@@ -78,21 +312,137 @@ pub fn add_file_bytes(name: String, content: Vec<u8>) -> Result<String, String>
     Ok(name)
 }
 
-fn build_scope(hierarchy: &wellen::Hierarchy, scope_ref: wellen::ScopeRef) -> HierarchyScope {
+fn var_type_name(var_type: wellen::VarType) -> &'static str {
+    use wellen::VarType;
+    match var_type {
+        VarType::Event => "event",
+        VarType::Integer => "integer",
+        VarType::Parameter => "parameter",
+        VarType::Real => "real",
+        VarType::Reg => "reg",
+        VarType::Supply0 => "supply0",
+        VarType::Supply1 => "supply1",
+        VarType::Time => "time",
+        VarType::Tri => "tri",
+        VarType::TriAnd => "triand",
+        VarType::TriOr => "trior",
+        VarType::TriReg => "trireg",
+        VarType::Tri0 => "tri0",
+        VarType::Tri1 => "tri1",
+        VarType::WAnd => "wand",
+        VarType::Wire => "wire",
+        VarType::WOr => "wor",
+        VarType::String => "string",
+        VarType::Port => "port",
+        VarType::SparseArray => "sparse_array",
+        VarType::RealTime => "realtime",
+        VarType::Bit => "bit",
+        VarType::Logic => "logic",
+        VarType::Int => "int",
+        VarType::ShortInt => "shortint",
+        VarType::LongInt => "longint",
+        VarType::Byte => "byte",
+        VarType::Enum => "enum",
+        VarType::ShortReal => "shortreal",
+        VarType::Boolean => "boolean",
+        VarType::BitVector => "bit_vector",
+        VarType::StdLogic => "std_logic",
+        VarType::StdLogicVector => "std_logic_vector",
+        VarType::StdULogic => "std_ulogic",
+        VarType::StdULogicVector => "std_ulogic_vector",
+    }
+}
+
+fn direction_name(direction: wellen::VarDirection) -> &'static str {
+    use wellen::VarDirection;
+    match direction {
+        VarDirection::Unknown => "unknown",
+        VarDirection::Implicit => "implicit",
+        VarDirection::Input => "input",
+        VarDirection::Output => "output",
+        VarDirection::InOut => "inout",
+        VarDirection::Buffer => "buffer",
+        VarDirection::Linkage => "linkage",
+    }
+}
+
+fn encoding_name(encoding: wellen::SignalEncoding) -> &'static str {
+    match encoding {
+        wellen::SignalEncoding::BitVector(_) => "bit_vector",
+        wellen::SignalEncoding::Real => "real",
+        wellen::SignalEncoding::String => "string",
+    }
+}
+
+fn build_var(file: &LoadedWave, var_ref: wellen::VarRef) -> HierarchyVar {
+    let hierarchy = file.wave.hierarchy();
+    let var = &hierarchy[var_ref];
+    let signal_ref = var.signal_ref();
+
+    HierarchyVar {
+        name: var.name(hierarchy).to_string(),
+        ref_: signal_ref.index(),
+        width: var.length(),
+        var_type: var_type_name(var.var_type()).to_string(),
+        direction: direction_name(var.direction()).to_string(),
+        index: var.index().map(|index| VarIndexRange {
+            msb: index.msb(),
+            lsb: index.lsb(),
+        }),
+        encoding: encoding_name(var.signal_encoding()).to_string(),
+        expression: None,
+        translation: file.signal_translations.get(&signal_ref.index()).cloned(),
+        is_alias: file.canonical_vars[signal_ref.index()] != Some(var_ref),
+    }
+}
+
+/// Positions in `virtual_signals` of the virtual signals placed in a scope.
+fn virtual_var_indices(file: &LoadedWave, scope_ref: Option<wellen::ScopeRef>) -> Vec<usize> {
+    file.virtual_signals
+        .iter()
+        .enumerate()
+        .filter(|(_, signal)| signal.as_ref().is_some_and(|signal| signal.scope == scope_ref))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn build_virtual_var(file: &LoadedWave, index: usize) -> HierarchyVar {
+    let signal_id = file.wave.hierarchy().num_unique_signals() + index;
+    let signal = file.virtual_signals[index].as_ref().expect("removed virtual signal");
+    HierarchyVar {
+        name: signal.name.clone(),
+        ref_: signal_id,
+        width: Some(signal.width()),
+        var_type: signal.var_type.to_string(),
+        direction: signal.direction.to_string(),
+        index: signal.index.map(|(msb, lsb)| VarIndexRange { msb, lsb }),
+        encoding: encoding_name(signal.encoding()).to_string(),
+        expression: Some(signal.expression.clone()),
+        translation: file.signal_translations.get(&signal_id).cloned(),
+        is_alias: false,
+    }
+}
+
+fn build_virtual_vars(file: &LoadedWave, scope_ref: Option<wellen::ScopeRef>) -> Vec<HierarchyVar> {
+    virtual_var_indices(file, scope_ref)
+        .into_iter()
+        .map(|index| build_virtual_var(file, index))
+        .collect()
+}
+
+fn build_scope(file: &LoadedWave, scope_ref: wellen::ScopeRef) -> HierarchyScope {
+    let hierarchy = file.wave.hierarchy();
     let scope = &hierarchy[scope_ref];
 
     let mut scope_vars = Vec::new();
     for var_ref in scope.vars(hierarchy) {
-        let var = &hierarchy[var_ref];
-        scope_vars.push(HierarchyVar {
-            name: var.name(hierarchy).to_string(),
-            ref_: var.signal_ref().index(),
-        });
+        scope_vars.push(build_var(file, var_ref));
     }
+    scope_vars.extend(build_virtual_vars(file, Some(scope_ref)));
 
     let mut sub_scopes = Vec::new();
     for sub_scope_ref in scope.scopes(hierarchy) {
-        sub_scopes.push(build_scope(hierarchy, sub_scope_ref));
+        sub_scopes.push(build_scope(file, sub_scope_ref));
     }
 
     HierarchyScope {
@@ -132,6 +482,170 @@ mod tests {
         assert_eq!(get_files().len(), 0);
     }
 
+    #[test]
+    #[serial]
+    fn test_waveform_info() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let info = get_waveform_info("simple.vcd".to_string()).expect("Failed to get waveform info");
+        assert_eq!(info.file_format, "VCD");
+        assert_eq!(info.version, "ModelSim Version 10.5b");
+        assert_eq!(info.date, "Sat Dec 26 15:33:14 2020");
+
+        let timescale = info.timescale.expect("Expected a timescale");
+        assert_eq!(timescale.factor, 1);
+        assert_eq!(timescale.unit, "ns");
+        assert_eq!(timescale.exponent, Some(-9));
+
+        assert_eq!(info.start_time, Some(0));
+        assert!(info.end_time.unwrap() > 0);
+        assert_eq!(info.scope_count, 2);
+        assert_eq!(info.var_count, 13);
+        // clk_out is shared between both scopes
+        assert_eq!(info.signal_count, 12);
+
+        assert!(get_waveform_info("missing.vcd".to_string()).is_err());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_hierarchy_var_metadata() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).expect("Failed to get hierarchy");
+        let tb = &hierarchy.scopes[0];
+        let t1 = &tb.scopes[0];
+
+        let clk = tb.vars.iter().find(|v| v.name == "clk").unwrap();
+        assert_eq!(clk.width, Some(1));
+        assert_eq!(clk.var_type, "reg");
+        assert_eq!(clk.direction, "unknown");
+        assert_eq!(clk.encoding, "bit_vector");
+        assert!(clk.index.is_none());
+        assert!(!clk.is_alias);
+
+        let r_reg = t1.vars.iter().find(|v| v.name == "r_reg").unwrap();
+        assert_eq!(r_reg.width, Some(3));
+        let index = r_reg.index.as_ref().expect("Expected an index range");
+        assert_eq!((index.msb, index.lsb), (2, 0));
+
+        let width = t1.vars.iter().find(|v| v.name == "WIDTH").unwrap();
+        assert_eq!(width.var_type, "parameter");
+        assert_eq!(width.width, Some(32));
+
+        // clk_out is dumped in both scopes with the same identifier code
+        let outer = tb.vars.iter().find(|v| v.name == "clk_out").unwrap();
+        let inner = t1.vars.iter().find(|v| v.name == "clk_out").unwrap();
+        assert_eq!(outer.ref_, inner.ref_);
+        assert!(!outer.is_alias);
+        assert!(inner.is_alias);
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_scope_children_pagination() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let root = get_scope_children("simple.vcd".to_string(), None, 0, 100).unwrap();
+        assert_eq!(root.total_scopes, 1);
+        assert_eq!(root.total_vars, 0);
+        let tb = &root.scopes[0];
+        assert_eq!(tb.name, "clkdiv2n_tb");
+        assert_eq!(tb.scope_type, "module");
+        assert_eq!((tb.scope_count, tb.var_count), (1, 3));
+        assert!(tb.has_children);
+
+        // The first page holds the child scope followed by the first var
+        let first = get_scope_children("simple.vcd".to_string(), Some(tb.ref_), 0, 2).unwrap();
+        assert_eq!(first.total_scopes, 1);
+        assert_eq!(first.total_vars, 3);
+        assert_eq!(first.scopes.len(), 1);
+        assert_eq!(first.scopes[0].name, "t1");
+        assert!(first.scopes[0].has_children);
+        assert_eq!(first.vars.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["clk"]);
+
+        let second = get_scope_children("simple.vcd".to_string(), Some(tb.ref_), 2, 2).unwrap();
+        assert!(second.scopes.is_empty());
+        assert_eq!(second.vars.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["reset", "clk_out"]);
+
+        let past_end = get_scope_children("simple.vcd".to_string(), Some(tb.ref_), 10, 2).unwrap();
+        assert!(past_end.scopes.is_empty() && past_end.vars.is_empty());
+
+        assert!(get_scope_children("simple.vcd".to_string(), Some(999), 0, 10).is_err());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_signals() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let query = |pattern: &str, mode: SearchMode| SearchQuery {
+            pattern: pattern.to_string(),
+            mode,
+            ..Default::default()
+        };
+
+        // A glob without a dot matches local names anywhere in the design
+        let clk = search_signals("simple.vcd".to_string(), query("clk*", SearchMode::Glob), 0, 100).unwrap();
+        assert_eq!(clk.total, 5);
+
+        let nested = search_signals("simple.vcd".to_string(), query("*.t1.clk*", SearchMode::Glob), 0, 100).unwrap();
+        let paths: Vec<_> = nested.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, ["clkdiv2n_tb.t1.clk", "clkdiv2n_tb.t1.clk_out", "clkdiv2n_tb.t1.clk_track"]);
+
+        let page = search_signals("simple.vcd".to_string(), query("*.t1.clk*", SearchMode::Glob), 1, 1).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.matches[0].path, "clkdiv2n_tb.t1.clk_out");
+
+        let regex = search_signals("simple.vcd".to_string(), query(r"^clkdiv2n_tb\.(clk|reset)$", SearchMode::Regex), 0, 100).unwrap();
+        assert_eq!(regex.total, 2);
+        assert!(search_signals("simple.vcd".to_string(), query("(", SearchMode::Regex), 0, 100).is_err());
+
+        let fuzzy = search_signals("simple.vcd".to_string(), query("rnxt", SearchMode::Fuzzy), 0, 100).unwrap();
+        assert_eq!(fuzzy.total, 3);
+        assert!(fuzzy.matches.iter().all(|m| m.name == "r_nxt" && m.score.is_some()));
+
+        let mut wide = query("*", SearchMode::Glob);
+        wide.min_width = Some(2);
+        wide.var_types = vec!["reg".to_string()];
+        let wide = search_signals("simple.vcd".to_string(), wide, 0, 100).unwrap();
+        assert_eq!(wide.total, 1);
+        assert_eq!(wide.matches[0].name, "r_reg");
+        assert_eq!(wide.matches[0].width, Some(3));
+
+        remove_file("simple.vcd".to_string());
+    }
+
     #[test]
     #[serial]
     fn test_signal_changes_with_real_file() {
@@ -162,11 +676,11 @@ mod tests {
         let signal_ref = test_signal_ref.unwrap();
 
         // Try to get signal changes - this should work with proper signal reference
-        let result = get_signal_changes("simple.vcd".to_string(), signal_ref, 0, 1000);
+        let result = get_signal_changes("simple.vcd".to_string(), signal_ref, 0, 1000, None);
         assert!(result.is_ok(), "Failed to get signal changes: {:?}", result.err());
         
         let changes = result.unwrap();
-        assert!(changes.len() > 0, "Expected at least one signal change");
+        assert!(!changes.is_empty(), "Expected at least one signal change");
 
         // Cleanup
         remove_file("simple.vcd".to_string());
@@ -174,179 +688,2408 @@ mod tests {
 
     #[test]
     #[serial]
-    fn test_signal_changes_includes_boundary_values() {
-        // Clear files first
+    fn test_signal_changes_batch_matches_single_queries() {
         {
             let mut files = OPENED_FILES.lock().unwrap();
             files.clear();
         }
 
-        // Load a real VCD file from examples
         let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
-        let res = add_file_bytes("simple.vcd".to_string(), vcd_content);
-        assert!(res.is_ok(), "Failed to load VCD file");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
 
-        // Get hierarchy to find signal refs
-        let hierarchy = get_hierarchy("simple.vcd".to_string()).expect("Failed to get hierarchy");
-        
-        // Find a variable with multiple changes (clk is a good candidate)
-        let mut test_signal_ref: Option<usize> = None;
-        for scope in &hierarchy.scopes {
-            for var in &scope.vars {
-                if var.name == "clk" {
-                    test_signal_ref = Some(var.ref_);
-                    break;
-                }
-            }
-            if test_signal_ref.is_some() {
-                break;
+        let signal_count = get_waveform_info("simple.vcd".to_string()).unwrap().signal_count;
+        let ids: Vec<usize> = (0..signal_count).collect();
+        let batch = get_signal_changes_batch("simple.vcd".to_string(), ids.clone(), 30, 100, None)
+            .expect("Failed to get batch signal changes");
+        assert_eq!(batch.len(), signal_count);
+
+        for id in ids {
+            let single = get_signal_changes("simple.vcd".to_string(), id, 30, 100, None).unwrap();
+            let batched = &batch[&id];
+            assert_eq!(single.len(), batched.len());
+            for (a, b) in single.iter().zip(batched) {
+                assert_eq!((a.time, &a.value), (b.time, &b.value));
             }
         }
 
-        assert!(test_signal_ref.is_some(), "No 'clk' variable found in hierarchy");
-        let signal_ref = test_signal_ref.unwrap();
+        // The id after the file's signals is the grouped r_nxt bus, the one after it is unused
+        assert!(get_signal_changes_batch("simple.vcd".to_string(), vec![0, signal_count + 1], 0, 10, None).is_err());
 
-        // First, get all changes to understand the signal
-        let all_changes = get_signal_changes("simple.vcd".to_string(), signal_ref, 0, u64::MAX)
-            .expect("Failed to get all signal changes");
-        
-        assert!(all_changes.len() >= 4, "Expected at least 4 changes for clk signal");
+        remove_file("simple.vcd".to_string());
+    }
 
-        // Now test with a range that excludes the first and last changes
-        // Choose a range like [30, 100] which should be in the middle
-        let range_start = 30;
-        let range_end = 100;
+    #[test]
+    #[serial]
+    fn test_signal_summary_buckets() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
 
-        let range_changes = get_signal_changes("simple.vcd".to_string(), signal_ref, range_start, range_end)
-            .expect("Failed to get range signal changes");
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
 
-        // Verify that the first returned change is before the range start
-        assert!(
-            range_changes.len() > 0,
-            "Expected at least one change in result"
-        );
-        
-        // The first change should be before or at the start (it's the boundary change)
-        assert!(
-            range_changes[0].time <= range_start,
-            "First change should be at or before range start. Got time={}, expected <= {}",
-            range_changes[0].time,
-            range_start
-        );
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).unwrap();
+        let tb = &hierarchy.scopes[0];
+        let clk = tb.vars.iter().find(|v| v.name == "clk").unwrap().ref_;
+        let r_reg = tb.scopes[0].vars.iter().find(|v| v.name == "r_reg").unwrap().ref_;
 
-        // At least one change should be within the range
-        let has_change_in_range = range_changes.iter().any(|c| c.time >= range_start && c.time <= range_end);
-        assert!(has_change_in_range, "Expected at least one change within the range");
+        // clk toggles every 10ns, so 40ns buckets are busy and 5ns buckets are stable
+        let coarse = get_signal_summary("simple.vcd".to_string(), clk, 0, 200, 5).unwrap();
+        assert_eq!(coarse.len(), 5);
+        assert_eq!((coarse[0].start, coarse[0].end), (0, 40));
+        assert!(coarse.iter().all(|b| b.busy && b.value.is_none()));
+        assert_eq!(coarse[1].transitions, 4);
+        assert_eq!((coarse[1].min, coarse[1].max), (Some(0.0), Some(1.0)));
 
-        // The last change should be after the range end (if there are changes after end)
-        // Find if there's a change after end in all_changes
-        let has_change_after_end = all_changes.iter().any(|c| c.time > range_end);
-        if has_change_after_end {
-            // Then our result should include it as the last element
-            assert!(
-                range_changes.last().unwrap().time > range_end,
-                "Last change should be after range end when such a change exists. Got time={}, expected > {}",
-                range_changes.last().unwrap().time,
-                range_end
-            );
-        }
+        let fine = get_signal_summary("simple.vcd".to_string(), clk, 10, 20, 2).unwrap();
+        assert!(fine.iter().all(|b| !b.busy));
+        assert_eq!(fine[0].value.as_deref(), Some("1"));
+        assert_eq!(fine[0].transitions, 1);
+        assert_eq!(fine[1].value.as_deref(), Some("1"));
+        assert_eq!(fine[1].transitions, 0);
+
+        // r_reg starts as x, then counts 0..=5 every 20ns from t=30 and wraps at t=130
+        let counter = get_signal_summary("simple.vcd".to_string(), r_reg, 0, 140, 2).unwrap();
+        assert_eq!((counter[0].min, counter[0].max), (Some(0.0), Some(2.0)));
+        assert_eq!((counter[1].min, counter[1].max), (Some(0.0), Some(5.0)));
+
+        assert!(get_signal_summary("simple.vcd".to_string(), clk, 100, 100, 4).is_err());
+        assert!(get_signal_summary("simple.vcd".to_string(), clk, 0, 100, 0).is_err());
 
-        // Cleanup
         remove_file("simple.vcd".to_string());
     }
-}
 
-pub fn get_hierarchy(filename: String) -> Result<HierarchyRoot, String> {
-    let files = OPENED_FILES.lock().unwrap();
-    let file = files.iter().find(|f| f.path.ends_with(&filename) || f.path == filename)
-        .ok_or_else(|| format!("File not found: {}", filename))?;
+    #[test]
+    #[serial]
+    fn test_signal_changes_match_full_scan() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
 
-    let waveform = &file.wave;
-    let hierarchy = waveform.hierarchy();
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
 
-    let root_vars = Vec::new(); // Usually empty for root/global
-    let mut root_scopes = Vec::new();
+        let signal_count = get_waveform_info("simple.vcd".to_string()).unwrap().signal_count;
+        for signal_id in 0..signal_count {
+            let all = get_signal_changes("simple.vcd".to_string(), signal_id, 0, u64::MAX, None).unwrap();
+            for (start, end) in [(0, 0), (0, 5), (5, 5), (12, 48), (30, 100), (95, 305), (400, 500), (1000, 2000)] {
+                // Reference: everything inside the window plus one change on either side
+                let before = all.iter().rposition(|c| c.time < start);
+                let after = all.iter().position(|c| c.time > end);
+                let from = before.unwrap_or_else(|| all.iter().position(|c| c.time >= start).unwrap_or(all.len()));
+                let to = after.map_or(all.len(), |i| i + 1);
+                let expected: Vec<_> = all[from.min(to)..to].iter().map(|c| (c.time, c.value.clone())).collect();
 
-    for scope_ref in hierarchy.scopes() {
-        root_scopes.push(build_scope(hierarchy, scope_ref));
+                let actual: Vec<_> = get_signal_changes("simple.vcd".to_string(), signal_id, start, end, None)
+                    .unwrap()
+                    .into_iter()
+                    .map(|c| (c.time, c.value))
+                    .collect();
+                assert_eq!(actual, expected, "signal {} window [{}, {}]", signal_id, start, end);
+            }
+        }
+
+        remove_file("simple.vcd".to_string());
     }
 
-    // Creating a synthetic "root" to hold everything
-    Ok(HierarchyRoot {
-        name: "root".to_string(),
-        ref_: 0, // Placeholder
-        vars: root_vars,
-        scopes: root_scopes,
-    })
-}
+    #[test]
+    #[serial]
+    fn test_find_signal_change() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
 
-#[wasm_bindgen]
-pub fn get_hierarchy_wasm(filename: String) -> Result<JsValue, String> {
-    let root = get_hierarchy(filename)?;
-    serde_wasm_bindgen::to_value(&root).map_err(|e| e.to_string())
-}
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
 
-use wellen::SignalRef;
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).unwrap();
+        let tb = &hierarchy.scopes[0];
+        let clk = tb.vars.iter().find(|v| v.name == "clk").unwrap().ref_;
+        let r_reg = tb.scopes[0].vars.iter().find(|v| v.name == "r_reg").unwrap().ref_;
+        let find = |id, from, direction, target| find_signal_change("simple.vcd".to_string(), id, from, direction, target).unwrap();
+        use NavigationDirection::{Backward, Forward};
 
-pub fn get_signal_changes(filename: String, signal_id: usize, start: u64, end: u64) -> Result<Vec<SignalChange>, String> {
-    let mut files = OPENED_FILES.lock().unwrap();
-    let file = files.iter_mut().find(|f| f.path.ends_with(&filename) || f.path == filename)
-        .ok_or_else(|| format!("File not found: {}", filename))?;
+        // clk rises at 10, 30, 50, ... and falls at 20, 40, ...
+        assert_eq!(find(clk, 10, Forward, NavigationTarget::Change), Some(20));
+        assert_eq!(find(clk, 25, Forward, NavigationTarget::RisingEdge), Some(30));
+        assert_eq!(find(clk, 30, Forward, NavigationTarget::RisingEdge), Some(50));
+        assert_eq!(find(clk, 30, Backward, NavigationTarget::RisingEdge), Some(10));
+        assert_eq!(find(clk, 30, Backward, NavigationTarget::FallingEdge), Some(20));
+        assert_eq!(find(clk, 10, Backward, NavigationTarget::Change), Some(0));
+        assert_eq!(find(clk, 0, Backward, NavigationTarget::Change), None);
 
-    let waveform = &mut file.wave;
+        // r_reg: x at 0, 0 at 5, then counts up every 20ns from t=30
+        let equals = |value: &str| NavigationTarget::Equals { value: value.to_string() };
+        assert_eq!(find(r_reg, 0, Forward, equals("3'd3")), Some(70));
+        assert_eq!(find(r_reg, 0, Forward, equals("0b11")), Some(70));
+        assert_eq!(find(r_reg, 200, Backward, equals("5")), Some(110));
+        let not_zero = NavigationTarget::NotEquals { value: "0".to_string() };
+        assert_eq!(find(r_reg, 5, Forward, not_zero.clone()), Some(30));
+        assert_eq!(find(r_reg, 30, Forward, not_zero), Some(50));
+        assert_eq!(find(r_reg, 10, Backward, NavigationTarget::Unknown), Some(0));
+        assert_eq!(find(r_reg, 0, Forward, NavigationTarget::Unknown), None);
 
-    let signal_ref = SignalRef::from_index(signal_id).ok_or("Invalid signal ID")?;
-    
-    // Load the signal data if not already loaded (required for lazy-loaded backends)
-    waveform.load_signals(&[signal_ref]);
+        let edge = find_signal_change("simple.vcd".to_string(), r_reg, 0, Forward, NavigationTarget::RisingEdge);
+        assert!(edge.is_err());
+        assert!(find_signal_change("simple.vcd".to_string(), r_reg, 0, Forward, equals("3'hg")).is_err());
 
-    let signal = waveform.get_signal(signal_ref).ok_or("Signal not found")?;
-    let time_table = waveform.time_table();
+        remove_file("simple.vcd".to_string());
+    }
 
-    let mut result = Vec::new();
-    let mut last_before_start: Option<SignalChange> = None;
-
-    for (time_idx, value) in signal.iter_changes() {
-         let time = time_table[time_idx as usize];
-
-         if time < start {
-             // Keep track of the last change before the start time
-             last_before_start = Some(SignalChange {
-                 time,
-                 value: format!("{}", value),
-             });
-             continue;
-         }
-
-         if time > end {
-             // Add the first change after the end time
-             result.push(SignalChange {
-                 time,
-                 value: format!("{}", value),
-             });
-             break;
-         }
-
-         result.push(SignalChange {
-             time,
-             value: format!("{}", value),
-         });
-    }
-
-    // Build final result with boundary change at the start
-    // More efficient than insert(0, ...) which requires shifting all elements
-    if let Some(before) = last_before_start {
-        let mut changes = Vec::with_capacity(result.len() + 1);
-        changes.push(before);
-        changes.extend(result);
-        Ok(changes)
+    #[test]
+    #[serial]
+    fn test_virtual_signals() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let signal_count = get_waveform_info(filename()).unwrap().signal_count;
+
+        // r_reg counts 0..=5 every 20ns from t=30, so it is 3 from 70 to 90 and again from 190
+        let three = add_virtual_signal(filename(), "is_three".to_string(), "clkdiv2n_tb.t1.r_reg == 3'd3".to_string()).unwrap();
+        // The first virtual id goes to the r_nxt bus grouped from its bits
+        assert_eq!(three.ref_, signal_count + 1);
+        assert_eq!(three.width, Some(1));
+        assert_eq!(three.expression.as_deref(), Some("clkdiv2n_tb.t1.r_reg == 3'd3"));
+        let changes: Vec<_> = get_signal_changes(filename(), three.ref_, 0, 100, None)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.time, c.value))
+            .collect();
+        assert_eq!(changes, vec![(0, "x".to_string()), (5, "0".to_string()), (70, "1".to_string()), (90, "0".to_string()), (190, "1".to_string())]);
+
+        // Separate bit vars are found by index, plain vectors are sliced
+        let bits = add_virtual_signal(
+            filename(),
+            "bits".to_string(),
+            "{clkdiv2n_tb.t1.r_nxt[2], clkdiv2n_tb.t1.r_reg[1:0], is_three}".to_string(),
+        )
+        .unwrap();
+        assert_eq!(bits.width, Some(4));
+        let at_75 = get_values_at(filename(), vec![bits.ref_, three.ref_], 75, None).unwrap();
+        assert_eq!(at_75[&bits.ref_].value.as_deref(), Some("1111"));
+        assert_eq!(
+            find_signal_change(filename(), three.ref_, 0, NavigationDirection::Forward, NavigationTarget::RisingEdge).unwrap(),
+            Some(70)
+        );
+
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        assert_eq!(hierarchy.vars.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), vec!["is_three", "bits"]);
+        let root = get_scope_children(filename(), None, 0, 10).unwrap();
+        assert_eq!(root.total_vars, 2);
+
+        assert!(add_virtual_signal(filename(), "is_three".to_string(), "1".to_string()).is_err());
+        assert_eq!(
+            add_virtual_signal(filename(), "bad".to_string(), "clkdiv2n_tb.nope".to_string()).err().as_deref(),
+            Some("Unknown signal: clkdiv2n_tb.nope")
+        );
+
+        remove_virtual_signal(filename(), three.ref_).unwrap();
+        assert!(get_signal_changes(filename(), three.ref_, 0, 100, None).is_err());
+        assert!(get_signal_changes(filename(), bits.ref_, 0, 100, None).is_ok());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_temporal() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let search = |condition, start, max_results| {
+            let query = TemporalQuery { condition, start, end: None, max_results, search_id: None };
+            search_temporal("simple.vcd".to_string(), query).unwrap()
+        };
+        let holds = |expression: &str| TemporalCondition::Holds { expression: expression.to_string() };
+        let spans = |results: &TemporalSearchResults| results.matches.iter().map(|m| (m.start, m.end)).collect::<Vec<_>>();
+
+        // r_reg is 3 from 70 and 190, each time while clk is high from 70 to 80 and 190 to 200
+        let condition = holds("clkdiv2n_tb.t1.r_reg == 3 && clkdiv2n_tb.clk");
+        let first = search(condition.clone(), 0, 1);
+        assert_eq!(spans(&first), vec![(70, Some(80))]);
+        assert_eq!(first.next_start, Some(80));
+        let rest = search(condition, 80, 10);
+        assert_eq!(spans(&rest)[0], (190, Some(200)));
+
+        // r_reg moves from 1 to 2 after 20ns, except at 510 where the trace ends
+        let unanswered = |within, clock: Option<&str>| TemporalCondition::Unanswered {
+            trigger: "clkdiv2n_tb.t1.r_reg == 1".to_string(),
+            response: "clkdiv2n_tb.t1.r_reg == 2".to_string(),
+            within,
+            clock: clock.map(str::to_string),
+        };
+        assert_eq!(spans(&search(unanswered(20, None), 0, 10)), vec![(510, None)]);
+        assert_eq!(spans(&search(unanswered(10, None), 0, 1)), vec![(30, Some(40))]);
+        // The first rising edge of clk after r_reg becomes 1 at 30 is at 50
+        let cycles = search(unanswered(1, Some("clkdiv2n_tb.clk")), 0, 10);
+        assert_eq!(spans(&cycles), vec![(510, None)]);
+        let forever = search(unanswered(u64::MAX, Some("clkdiv2n_tb.clk")), 0, 10);
+        assert_eq!(spans(&forever), vec![(510, None)]);
+
+        // Cancelling a search that is not running has no effect on a later one
+        let query = |search_id| TemporalQuery { condition: holds("clkdiv2n_tb.clk"), start: 0, end: None, max_results: 1000, search_id };
+        cancel_temporal_search(7);
+        assert!(search_temporal("simple.vcd".to_string(), query(Some(7))).is_ok());
+
+        // Holding the files keeps the search waiting while it is cancelled
+        let (files, query) = (OPENED_FILES.lock().unwrap(), query(Some(7)));
+        let running = std::thread::spawn(move || search_temporal("simple.vcd".to_string(), query));
+        while !ACTIVE_SEARCHES.lock().unwrap().iter().any(|(id, _)| *id == 7) {
+            std::thread::yield_now();
+        }
+        cancel_temporal_search(7);
+        drop(files);
+        assert_eq!(running.join().unwrap().err().as_deref(), Some("Search cancelled"));
+        assert!(ACTIVE_SEARCHES.lock().unwrap().is_empty());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_assertion() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let check = |property: &str, failures_only| {
+            let property = format!("@(posedge clkdiv2n_tb.clk) {}", property);
+            check_assertion("simple.vcd".to_string(), property, failures_only).unwrap()
+        };
+        let attempts = |report: &AssertionReport| report.attempts.iter().map(|a| (a.start, a.end, a.status)).collect::<Vec<_>>();
+
+        // r_reg is sampled just before each rising edge of clk, so it reads 5 at 130 and
+        // wraps to 0 for the next edge at 150
+        let wraps = check("clkdiv2n_tb.t1.r_reg == 5 |=> clkdiv2n_tb.t1.r_reg == 0", false);
+        assert_eq!((wraps.passed, wraps.failed), (4, 0));
+        assert_eq!(attempts(&wraps)[0], (130, 150, AttemptStatus::Pass));
+
+        let skips = check("clkdiv2n_tb.t1.r_reg == 1 |-> ##1 clkdiv2n_tb.t1.r_reg == 3", true);
+        assert_eq!(skips.failed, 4);
+        assert_eq!(attempts(&skips)[0], (50, 70, AttemptStatus::Fail));
+
+        // Without implication every tick is an attempt; reset is high until 15
+        let zero = check("disable iff (clkdiv2n_tb.reset) clkdiv2n_tb.t1.r_reg == 0", false);
+        assert_eq!((zero.passed, zero.failed, zero.disabled), (5, 20, 1));
+        assert_eq!(
+            attempts(&zero)[..3],
+            [(10, 10, AttemptStatus::Disabled), (30, 30, AttemptStatus::Pass), (50, 50, AttemptStatus::Fail)]
+        );
+
+        // clk_out rises at 130, first sampled high at 150; r_reg is sampled as 4 at 230
+        let rose = check("$rose(clkdiv2n_tb.clk_out) |-> ##[1:$] clkdiv2n_tb.t1.r_reg == 4", false);
+        assert_eq!(attempts(&rose)[0], (150, 230, AttemptStatus::Pass));
+
+        // The trace ends at 510, before the last attempt can be decided
+        let late = check("clkdiv2n_tb.t1.r_reg == 5 |=> ##1 clkdiv2n_tb.t1.r_reg == 1", false);
+        assert_eq!((late.passed, late.incomplete), (3, 1));
+        assert_eq!(attempts(&late)[3], (490, 510, AttemptStatus::Incomplete));
+
+        // reset is x before the first falling edge of clk and stable after it
+        let stable = check_assertion(
+            "simple.vcd".to_string(),
+            "@(negedge clkdiv2n_tb.clk) $stable(clkdiv2n_tb.reset)".to_string(),
+            true,
+        )
+        .unwrap();
+        assert_eq!((stable.passed, stable.failed), (24, 1));
+        assert_eq!(attempts(&stable), vec![(20, 20, AttemptStatus::Fail)]);
+
+        let huge = check("clkdiv2n_tb.t1.r_reg == 5 |-> ##[0:18446744073709551615] clkdiv2n_tb.t1.r_reg == 0", false);
+        assert_eq!((huge.passed, huge.failed), (4, 0));
+
+        let error = |property: &str| check_assertion("simple.vcd".to_string(), property.to_string(), false).err();
+        assert_eq!(error("clkdiv2n_tb.clk").as_deref(), Some("Assertions need a sampling clock, as in @(posedge clk)"));
+        assert_eq!(
+            error("@(posedge clkdiv2n_tb.clk) clkdiv2n_tb.clk ##1").as_deref(),
+            Some("Sequence must end with an expression")
+        );
+        let virtual_error = add_virtual_signal("simple.vcd".to_string(), "rose".to_string(), "$rose(clkdiv2n_tb.clk)".to_string());
+        assert_eq!(
+            virtual_error.err().as_deref(),
+            Some("$rose, $fell, $stable and $past need a sampling clock and only work in assertions")
+        );
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_bus_grouping_and_fields() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let values = |signal_id: usize| -> Vec<(u64, String)> {
+            get_signal_changes(filename(), signal_id, 0, 60, None).unwrap().into_iter().map(|c| (c.time, c.value)).collect()
+        };
+
+        // r_nxt is dumped as r_nxt[2], r_nxt[1] and r_nxt[0], listed after them as one bus
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let t1 = &hierarchy.scopes[0].scopes[0];
+        let grouped = t1.vars.last().unwrap();
+        assert_eq!(grouped.name, "r_nxt");
+        assert_eq!(grouped.width, Some(3));
+        assert_eq!(grouped.var_type, "wire");
+        assert_eq!(grouped.index.as_ref().map(|index| (index.msb, index.lsb)), Some((2, 0)));
+        assert_eq!(
+            grouped.expression.as_deref(),
+            Some("{clkdiv2n_tb.t1.r_nxt[2], clkdiv2n_tb.t1.r_nxt[1], clkdiv2n_tb.t1.r_nxt[0]}")
+        );
+        assert!(hierarchy.vars.is_empty());
+        let children = get_scope_children(filename(), Some(t1.ref_), 0, 20).unwrap();
+        assert_eq!(children.vars.last().map(|var| var.ref_), Some(grouped.ref_));
+        assert_eq!(values(grouped.ref_)[..3], [(0, "xxx".to_string()), (5, "001".to_string()), (30, "010".to_string())]);
+
+        // The grouped bus can be used by path, with its declared indices
+        let high = add_virtual_signal(filename(), "high".to_string(), "clkdiv2n_tb.t1.r_nxt[2:1]".to_string()).unwrap();
+        assert_eq!(values(high.ref_)[..3], [(0, "xx".to_string()), (5, "00".to_string()), (30, "01".to_string())]);
+
+        let r_reg = t1.vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+        let fields = vec![
+            BusField { name: "top".to_string(), msb: 2, lsb: 1 },
+            BusField { name: "bottom".to_string(), msb: 0, lsb: 0 },
+        ];
+        let field_vars = add_bus_fields(filename(), r_reg, fields).unwrap();
+        assert_eq!(field_vars[0].expression.as_deref(), Some("clkdiv2n_tb.t1.r_reg[2:1]"));
+        assert_eq!(values(field_vars[1].ref_)[..3], [(0, "x".to_string()), (5, "0".to_string()), (30, "1".to_string())]);
+        let bad = vec![
+            BusField { name: "ok".to_string(), msb: 1, lsb: 0 },
+            BusField { name: "bad".to_string(), msb: 3, lsb: 0 },
+        ];
+        assert_eq!(add_bus_fields(filename(), r_reg, bad).err().as_deref(), Some("Index 3 is outside [2:0]"));
+        assert_eq!(get_hierarchy(filename()).unwrap().vars.len(), 3);
+
+        let bits: Vec<usize> = t1.vars.iter().filter(|var| var.name == "r_nxt" && var.width == Some(1)).map(|var| var.ref_).collect();
+        let reversed = assemble_bus(filename(), "reversed".to_string(), bits.into_iter().rev().collect()).unwrap();
+        assert_eq!(reversed.width, Some(3));
+        assert_eq!(values(reversed.ref_)[1], (5, "100".to_string()));
+
+        // The summary of t1 counts the buses placed in it, as listing its children does
+        let top = get_scope_children(filename(), Some(hierarchy.scopes[0].ref_), 0, 20).unwrap();
+        let summary = top.scopes.iter().find(|scope| scope.ref_ == t1.ref_).unwrap();
+        let children = get_scope_children(filename(), Some(t1.ref_), 0, 20).unwrap();
+        assert_eq!(summary.var_count, children.total_vars);
+        assert_eq!(summary.var_count, t1.vars.len());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_translation() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+        assert!(get_translation_tables(filename()).unwrap().is_empty());
+
+        let filter = "# r_reg states\n0 S_IDLE\n1 S_START\n2 ?blue?S_WAIT_ACK\n";
+        assert_eq!(add_translation_table(filename(), "states".to_string(), filter.to_string(), TranslationFormat::Filter), Ok(3));
+        set_signal_translation(filename(), r_reg, Some("states".to_string())).unwrap();
+
+        // Values the table does not list, like the 3 after the window, use the requested format
+        let values: Vec<String> = get_signal_changes(filename(), r_reg, 0, 60, Some(ValueFormat::Unsigned))
+            .unwrap()
+            .into_iter()
+            .map(|c| c.value)
+            .collect();
+        assert_eq!(values, vec!["x", "S_IDLE", "S_START", "S_WAIT_ACK", "3"]);
+        let at_55 = get_values_at(filename(), vec![r_reg], 55, None).unwrap();
+        assert_eq!(at_55[&r_reg].value.as_deref(), Some("S_WAIT_ACK"));
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let var = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.ref_ == r_reg).unwrap();
+        assert_eq!(var.translation.as_deref(), Some("states"));
+
+        let json = r#"{"0b000": "ZERO"}"#;
+        add_translation_table(filename(), "states".to_string(), json.to_string(), TranslationFormat::Json).unwrap();
+        let batch = get_signal_changes_batch(filename(), vec![r_reg], 0, 30, None).unwrap();
+        assert_eq!(batch[&r_reg][1].value, "ZERO");
+        assert_eq!(get_translation_tables(filename()).unwrap(), vec!["states"]);
+
+        set_signal_translation(filename(), r_reg, None).unwrap();
+        assert_eq!(get_signal_changes(filename(), r_reg, 0, 10, None).unwrap()[1].value, "000");
+        assert_eq!(
+            set_signal_translation(filename(), r_reg, Some("nope".to_string())).err().as_deref(),
+            Some("Translation table not found: nope")
+        );
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    /// Labels values by their length and fails on unknown ones, or when the files are
+    /// locked, counting the values it gets.
+    struct LengthTranslator(Arc<Mutex<usize>>);
+
+    impl ValueTranslator for LengthTranslator {
+        fn translate(&mut self, value: &str) -> Result<String, String> {
+            *self.0.lock().unwrap() += 1;
+            if OPENED_FILES.try_lock().is_err() {
+                return Err("Translating with the files locked".to_string());
+            }
+            if value.contains('x') {
+                return Err(format!("Cannot translate {}", value));
+            }
+            Ok(format!("len {}", value.len()))
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_translator() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+
+        let calls = Arc::new(Mutex::new(0));
+        add_translation_table(filename(), "states".to_string(), "0 S_IDLE\n".to_string(), TranslationFormat::Filter).unwrap();
+        set_signal_translation(filename(), r_reg, Some("states".to_string())).unwrap();
+        set_signal_translator(filename(), r_reg, Some(Box::new(LengthTranslator(calls.clone())))).unwrap();
+
+        // The translator gets the formatted values and takes precedence over the table
+        let changes = get_signal_changes(filename(), r_reg, 10, 30, None).unwrap();
+        assert_eq!(changes.iter().map(|c| c.value.as_str()).collect::<Vec<_>>(), vec!["len 3"; 3]);
+        assert_eq!(*calls.lock().unwrap(), 3);
+        // Values the translator fails on are left formatted
+        assert_eq!(get_values_at(filename(), vec![r_reg], 0, None).unwrap()[&r_reg].value.as_deref(), Some("xxx"));
+
+        set_signal_translator(filename(), r_reg, None).unwrap();
+        assert_eq!(get_signal_changes(filename(), r_reg, 10, 20, None).unwrap()[0].value, "S_IDLE");
+        assert!(set_signal_translator(filename(), usize::MAX, None).is_err());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_analog_samples() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd = "$timescale 1ns $end\n$scope module top $end\n$var real 64 ! v $end\n$upscope $end\n$enddefinitions $end\n\
+            #0\nr0\n!\n#10\nr1.5\n!\n#20\nr-2.25\n!\n#30\nr7\n!\n#40\nr0.5\n!\n#50\nr1\n!\n#55\nr3\n!\n";
+        add_file_bytes("analog.vcd".to_string(), vcd.as_bytes().to_vec()).expect("Failed to load VCD file");
+        let filename = || "analog.vcd".to_string();
+        let v = get_hierarchy(filename()).unwrap().scopes[0].vars[0].ref_;
+
+        let window = get_analog_samples(filename(), v, 15, 35, None).unwrap();
+        assert_eq!(window.interpolation, Interpolation::Step);
+        let times: Vec<u64> = window.samples.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![10, 20, 30, 40]);
+        assert_eq!(window.samples[1].value, Some(-2.25));
+        // The sample after the window does not count towards its range
+        assert_eq!((window.min, window.max), (Some(-2.25), Some(7.0)));
+        assert!(!window.decimated);
+
+        // Decimated to the extremes of two spans, keeping both peaks
+        let window = get_analog_samples(filename(), v, 0, 60, Some(6)).unwrap();
+        assert!(window.decimated);
+        let samples: Vec<(u64, Option<f64>)> = window.samples.iter().map(|s| (s.time, s.value)).collect();
+        assert_eq!(samples, vec![(10, Some(1.5)), (20, Some(-2.25)), (30, Some(7.0)), (40, Some(0.5))]);
+        assert_eq!((window.min, window.max), (Some(-2.25), Some(7.0)));
+
+        set_interpolation(filename(), v, Interpolation::Linear).unwrap();
+        assert_eq!(get_analog_samples(filename(), v, 0, 10, None).unwrap().interpolation, Interpolation::Linear);
+        assert!(set_interpolation(filename(), usize::MAX, Interpolation::Step).is_err());
+
+        remove_file("analog.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_statistics() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let id = |name: &str| hierarchy.scopes[0].vars.iter().find(|var| var.name == name).unwrap().ref_;
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+
+        let clk = get_signal_statistics(filename(), id("clk"), 0, 100).unwrap();
+        assert_eq!(clk.transitions, 9);
+        assert_eq!((clk.high_time, clk.low_time, clk.duty_cycle), (Some(50), Some(50), Some(0.5)));
+        assert_eq!((clk.unknown_time, clk.distinct_values), (0, 2));
+        assert_eq!(clk.longest_stable, Some(StableInterval { start: 0, end: 10, value: "0".to_string() }));
+
+        // x until 5, 1 until 15, then 0
+        let reset = get_signal_statistics(filename(), id("reset"), 0, 100).unwrap();
+        assert_eq!(reset.transitions, 2);
+        assert_eq!((reset.high_time, reset.low_time, reset.unknown_time), (Some(10), Some(85), 5));
+        assert_eq!(reset.longest_stable, Some(StableInterval { start: 15, end: 100, value: "0".to_string() }));
+
+        // 0 from 5, then 1 to 5 every 20 from 30; the window starts at the value held at 40
+        let counter = get_signal_statistics(filename(), r_reg, 40, 110).unwrap();
+        assert_eq!(counter.transitions, 3);
+        assert_eq!((counter.high_time, counter.duty_cycle), (None, None));
+        assert_eq!((counter.min, counter.max, counter.distinct_values), (Some(1.0), Some(4.0), 4));
+        assert_eq!(counter.mean, Some((1.0 * 10.0 + 2.0 * 20.0 + 3.0 * 20.0 + 4.0 * 20.0) / 70.0));
+        assert!(get_signal_statistics(filename(), r_reg, 10, 10).is_err());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_clock_detection() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let id = |name: &str| hierarchy.scopes[0].vars.iter().find(|var| var.name == name).unwrap().ref_;
+
+        let clk = get_clock_info(filename(), id("clk")).unwrap().expect("clk is a clock");
+        assert_eq!((clk.period, clk.min_period, clk.max_period), (20.0, 20, 20));
+        assert_eq!(clk.frequency, Some(1.0 / 20e-9));
+        assert_eq!(clk.duty_cycle, 0.5);
+        assert!(clk.gated.is_empty());
+        assert_eq!(clk.cycles, 26);
+        assert!(get_clock_info(filename(), id("reset")).unwrap().is_none());
+
+        let clocks = detect_clocks(filename()).unwrap();
+        let paths: Vec<&str> = clocks.iter().map(|clock| clock.path.as_str()).collect();
+        assert!(paths.contains(&"clkdiv2n_tb.clk"));
+        assert!(!paths.contains(&"clkdiv2n_tb.reset"));
+        remove_file("simple.vcd".to_string());
+
+        // Stops low after 30 and for good after 75, at the end of the trace at 100
+        let vcd = "$timescale 1ps $end\n$scope module top $end\n$var wire 1 ! clk $end\n$var wire 1 \" d $end\n\
+            $upscope $end\n$enddefinitions $end\n#0\n0!\n0\"\n#5\n1!\n#10\n0!\n#15\n1!\n#20\n0!\n#25\n1!\n#30\n0!\n\
+            #60\n1!\n#65\n0!\n#70\n1!\n#75\n0!\n#100\n1\"\n";
+        add_file_bytes("gated.vcd".to_string(), vcd.as_bytes().to_vec()).expect("Failed to load VCD file");
+        let clk = get_clock_info("gated.vcd".to_string(), 0).unwrap().expect("clk is a clock");
+        assert_eq!((clk.period, clk.cycles, clk.frequency), (10.0, 5, Some(1e11)));
+        assert_eq!(clk.gated, vec![GatedInterval { start: 30, end: 60 }, GatedInterval { start: 75, end: 100 }]);
+        remove_file("gated.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_cycle_axis() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let clk = hierarchy.scopes[0].vars.iter().find(|var| var.name == "clk").unwrap().ref_;
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+
+        // Rising edges at 10, 30, 50...
+        let info = get_cycle_axis(filename(), clk, ClockEdge::Rising).unwrap();
+        assert_eq!((info.cycles, info.first_edge), (26, 10));
+        assert_eq!(times_to_cycles(filename(), clk, ClockEdge::Rising, vec![10, 20, 50]).unwrap(), vec![0.0, 0.5, 2.0]);
+        assert_eq!(times_to_cycles(filename(), clk, ClockEdge::Falling, vec![30]).unwrap(), vec![0.5]);
+        assert_eq!(times_to_cycles(filename(), clk, ClockEdge::Both, vec![30]).unwrap(), vec![2.0]);
+
+        // Cycles 1 to 3 as a window of changes
+        let window = cycles_to_times(filename(), clk, ClockEdge::Rising, vec![1.0, 3.0]).unwrap();
+        assert_eq!(window, vec![30, 70]);
+        let values: Vec<String> = get_signal_changes(filename(), r_reg, window[0], window[1], Some(ValueFormat::Unsigned))
+            .unwrap()
+            .into_iter()
+            .map(|c| c.value)
+            .collect();
+        assert_eq!(values, vec!["0", "1", "2", "3", "4"]);
+
+        assert_eq!(
+            get_cycle_axis(filename(), r_reg, ClockEdge::Rising).err().as_deref(),
+            Some("The cycle axis needs a 1-bit clock")
+        );
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_sample_on_clock() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let id = |name: &str| hierarchy.scopes[0].vars.iter().find(|var| var.name == name).unwrap().ref_;
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+
+        // Rising edges at 10, 30, 50 and 70; r_reg changes on them, so each edge sees the previous value
+        let query = |offset: usize| SampleQuery {
+            clock_id: id("clk"),
+            edge: ClockEdge::Rising,
+            signal_ids: vec![r_reg, id("reset")],
+            start: 0,
+            end: Some(70),
+            offset,
+            limit: 2,
+            format: Some(ValueFormat::Unsigned),
+        };
+        let table = sample_on_clock(filename(), query(0)).unwrap();
+        assert_eq!(table.total_rows, 4);
+        let values = |row: &SampledRow| row.values.iter().map(|v| v.as_deref().unwrap_or("-")).collect::<Vec<_>>().join(" ");
+        assert_eq!((table.rows[0].time, table.rows[0].cycle, values(&table.rows[0]).as_str()), (10, 0, "0 1"));
+        assert_eq!(values(&table.rows[1]), "0 0");
+
+        let table = sample_on_clock(filename(), query(2)).unwrap();
+        let rows: Vec<(usize, String)> = table.rows.iter().map(|row| (row.cycle, values(row))).collect();
+        assert_eq!(rows, vec![(2, "1 0".to_string()), (3, "2 0".to_string())]);
+        assert!(sample_on_clock(filename(), query(4)).unwrap().rows.is_empty());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_event_list() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+        let hierarchy = get_hierarchy(filename()).unwrap();
+        let clk = hierarchy.scopes[0].vars.iter().find(|var| var.name == "clk").unwrap().ref_;
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|var| var.name == "r_reg").unwrap().ref_;
+        let query = |cursor: Option<u64>, direction: NavigationDirection| EventListQuery {
+            signal_ids: vec![clk, r_reg],
+            cursor,
+            direction,
+            limit: 3,
+            format: None,
+        };
+        let row = |time: u64, changed: Vec<usize>, values: [&str; 2]| EventRow {
+            time,
+            changed,
+            values: values.iter().map(|v| Some(v.to_string())).collect(),
+        };
+
+        let page = get_event_list(filename(), query(Some(25), NavigationDirection::Forward)).unwrap();
+        let expected = vec![row(30, vec![0, 1], ["1", "001"]), row(40, vec![0], ["0", "001"]), row(50, vec![0, 1], ["1", "010"])];
+        assert_eq!(page.rows, expected);
+        assert_eq!((page.previous_cursor, page.next_cursor), (Some(30), Some(60)));
+
+        // Back from the first row of that page: the changes at 10 and 20, then those at 0 and 5
+        let page = get_event_list(filename(), query(Some(30), NavigationDirection::Backward)).unwrap();
+        assert_eq!(page.rows.iter().map(|row| row.time).collect::<Vec<_>>(), vec![5, 10, 20]);
+        assert_eq!((page.previous_cursor, page.next_cursor), (Some(5), Some(30)));
+        let page = get_event_list(filename(), query(Some(5), NavigationDirection::Backward)).unwrap();
+        assert_eq!(page.rows, vec![row(0, vec![0, 1], ["0", "xxx"])]);
+        assert_eq!((page.previous_cursor, page.next_cursor), (None, Some(5)));
+
+        let page = get_event_list(filename(), query(None, NavigationDirection::Backward)).unwrap();
+        assert_eq!((page.rows.len(), page.next_cursor), (3, None));
+
+        let csv = export_event_list_csv(filename(), vec![clk, r_reg], 0, Some(10), Some(ValueFormat::Unsigned)).unwrap();
+        assert_eq!(csv, "time,clkdiv2n_tb.clk,clkdiv2n_tb.t1.r_reg\n0,0,x\n5,0,0\n10,1,0\n");
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_changes_near() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+
+        // The clocks rise at 30 and the counter steps on them
+        let near = get_changes_near(filename(), None, 32, 5, 3, Some(ValueFormat::Unsigned)).unwrap();
+        assert_eq!(near.total, 5);
+        let paths: Vec<&str> = near.changes.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(paths, vec!["clkdiv2n_tb.clk", "clkdiv2n_tb.t1.clk", "clkdiv2n_tb.t1.r_nxt[0]"]);
+        assert_eq!((near.changes[0].time, near.changes[0].distance), (30, 2));
+
+        // Under t1 only, where the counter wraps from 5 to 0 and clk_out rises
+        let t1 = get_hierarchy(filename()).unwrap().scopes[0].scopes[0].ref_;
+        let near = get_changes_near(filename(), Some(t1), 130, 0, 100, Some(ValueFormat::Unsigned)).unwrap();
+        let r_reg = near.changes.iter().find(|change| change.path == "clkdiv2n_tb.t1.r_reg").unwrap();
+        assert_eq!((r_reg.old_value.as_deref(), r_reg.new_value.as_str()), (Some("5"), "0"));
+        assert!(near.changes.iter().any(|change| change.path == "clkdiv2n_tb.t1.clk_out"));
+        assert!(near.changes.iter().all(|change| change.path.starts_with("clkdiv2n_tb.t1.")));
+
+        // A wide window counts every transition and keeps the closest
+        let near = get_changes_near(filename(), Some(t1), 45, 20, 100, None).unwrap();
+        let clk = near.changes.iter().find(|change| change.path == "clkdiv2n_tb.t1.clk").unwrap();
+        assert_eq!((clk.time, clk.transitions), (40, 4));
+        assert!(get_changes_near(filename(), Some(usize::MAX), 0, 0, 1, None).is_err());
+
+        // The signals loaded for the query are unloaded again
+        let files = OPENED_FILES.lock().unwrap();
+        let wave = &files[0].wave;
+        let signal_refs = (0..wave.hierarchy().num_unique_signals()).filter_map(wellen::SignalRef::from_index);
+        assert!(signal_refs.clone().count() > 0);
+        assert!(signal_refs.into_iter().all(|signal_ref| wave.get_signal(signal_ref).is_none()));
+        drop(files);
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_activity_map() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+        let filename = || "simple.vcd".to_string();
+
+        // reset goes to 1 at 5 and back to 0 at 15, both in the first bucket
+        let reset = get_activity_map(filename(), ActivitySource::Signal { signal_id: 1 }, 4).unwrap();
+        assert_eq!(reset.transitions, vec![2, 0, 0, 0]);
+        let clk = get_activity_map(filename(), ActivitySource::Signal { signal_id: 0 }, 4).unwrap();
+        assert!(clk.transitions.iter().all(|count| *count > 0));
+
+        // A scope sums its signals, counting each once
+        let t1 = get_hierarchy(filename()).unwrap().scopes[0].scopes[0].ref_;
+        let scope = get_activity_map(filename(), ActivitySource::Scope { scope_ref: Some(t1) }, 4).unwrap();
+        let whole = get_activity_map(filename(), ActivitySource::Scope { scope_ref: None }, 4).unwrap();
+        assert!(scope.signals < whole.signals);
+        for (part, all) in scope.transitions.iter().zip(&whole.transitions) {
+            assert!(part <= all);
+        }
+
+        // A scope unloads the signals it loaded and keeps those loaded before
+        let files = OPENED_FILES.lock().unwrap();
+        let wave = &files[0].wave;
+        let loaded: Vec<usize> = (0..wave.hierarchy().num_unique_signals())
+            .filter(|index| wave.get_signal(wellen::SignalRef::from_index(*index).unwrap()).is_some())
+            .collect();
+        assert_eq!(loaded, vec![0, 1]);
+        drop(files);
+
+        // Only the latest bucket count of a source is kept, and large counts are capped
+        let times = OPENED_FILES.lock().unwrap()[0].wave.time_table().len();
+        let capped = get_activity_map(filename(), ActivitySource::Signal { signal_id: 0 }, usize::MAX).unwrap();
+        assert_eq!(capped.transitions.len(), times);
+        assert_eq!(capped.transitions.iter().sum::<usize>(), clk.transitions.iter().sum::<usize>());
+        assert_eq!(OPENED_FILES.lock().unwrap()[0].activity_maps.len(), 4);
+
+        assert!(get_activity_map(filename(), ActivitySource::Scope { scope_ref: Some(usize::MAX) }, 4).is_err());
+        assert!(get_activity_map(filename(), ActivitySource::Signal { signal_id: 0 }, 0).is_err());
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_changes_packed_matches_strings() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).unwrap();
+        let clk = hierarchy.scopes[0].vars.iter().find(|v| v.name == "clk").unwrap().ref_;
+
+        let changes = get_signal_changes("simple.vcd".to_string(), clk, 30, 100, None).unwrap();
+        let bytes = get_signal_changes_packed("simple.vcd".to_string(), clk, 30, 100).unwrap();
+
+        assert_eq!(bytes[0], packed::KIND_BITS);
+        assert_eq!(bytes[1], 1);
+        let count = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        assert_eq!(count, changes.len());
+
+        let values_at = 16 + count * 8;
+        assert_eq!(bytes.len(), values_at + count);
+        for (i, change) in changes.iter().enumerate() {
+            let time = u64::from_le_bytes(bytes[16 + i * 8..24 + i * 8].try_into().unwrap());
+            assert_eq!(time, change.time);
+            let bit = bytes[values_at + i] >> 7;
+            assert_eq!(bit.to_string(), change.value);
+        }
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_changes_with_format() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).unwrap();
+        let r_reg = hierarchy.scopes[0].scopes[0].vars.iter().find(|v| v.name == "r_reg").unwrap().ref_;
+
+        let values = |format: Option<ValueFormat>| -> Vec<String> {
+            get_signal_changes("simple.vcd".to_string(), r_reg, 0, 120, format)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.value)
+                .collect()
+        };
+        assert_eq!(values(None), ["xxx", "000", "001", "010", "011", "100", "101", "000"]);
+        assert_eq!(values(Some(ValueFormat::Unsigned)), ["x", "0", "1", "2", "3", "4", "5", "0"]);
+        assert_eq!(values(Some(ValueFormat::Signed)), ["x", "0", "1", "2", "3", "-4", "-3", "0"]);
+        assert_eq!(values(Some(ValueFormat::Hex)), ["x", "0", "1", "2", "3", "4", "5", "0"]);
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_values_at_time() {
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        add_file_bytes("simple.vcd".to_string(), vcd_content).expect("Failed to load VCD file");
+
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).unwrap();
+        let tb = &hierarchy.scopes[0];
+        let clk = tb.vars.iter().find(|v| v.name == "clk").unwrap().ref_;
+        let r_reg = tb.scopes[0].vars.iter().find(|v| v.name == "r_reg").unwrap().ref_;
+
+        let values = get_values_at("simple.vcd".to_string(), vec![clk, r_reg], 75, Some(ValueFormat::Unsigned)).unwrap();
+        let clk_value = &values[&clk];
+        assert_eq!(clk_value.value.as_deref(), Some("1"));
+        assert_eq!((clk_value.previous_change, clk_value.next_change), (Some(70), Some(80)));
+        let r_reg_value = &values[&r_reg];
+        assert_eq!(r_reg_value.value.as_deref(), Some("3"));
+        assert_eq!((r_reg_value.previous_change, r_reg_value.next_change), (Some(70), Some(90)));
+
+        // A change exactly at the queried time is already in effect
+        let at_edge = get_values_at("simple.vcd".to_string(), vec![clk], 80, None).unwrap();
+        assert_eq!(at_edge[&clk].value.as_deref(), Some("0"));
+        assert_eq!(at_edge[&clk].previous_change, Some(80));
+
+        let after_end = get_values_at("simple.vcd".to_string(), vec![clk], u64::MAX, None).unwrap();
+        assert!(after_end[&clk].value.is_some());
+        assert_eq!(after_end[&clk].next_change, None);
+
+        remove_file("simple.vcd".to_string());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signal_changes_includes_boundary_values() {
+        // Clear files first
+        {
+            let mut files = OPENED_FILES.lock().unwrap();
+            files.clear();
+        }
+
+        // Load a real VCD file from examples
+        let vcd_content = std::fs::read("../examples/simple.vcd").expect("Failed to read simple.vcd");
+        let res = add_file_bytes("simple.vcd".to_string(), vcd_content);
+        assert!(res.is_ok(), "Failed to load VCD file");
+
+        // Get hierarchy to find signal refs
+        let hierarchy = get_hierarchy("simple.vcd".to_string()).expect("Failed to get hierarchy");
+        
+        // Find a variable with multiple changes (clk is a good candidate)
+        let mut test_signal_ref: Option<usize> = None;
+        for scope in &hierarchy.scopes {
+            for var in &scope.vars {
+                if var.name == "clk" {
+                    test_signal_ref = Some(var.ref_);
+                    break;
+                }
+            }
+            if test_signal_ref.is_some() {
+                break;
+            }
+        }
+
+        assert!(test_signal_ref.is_some(), "No 'clk' variable found in hierarchy");
+        let signal_ref = test_signal_ref.unwrap();
+
+        // First, get all changes to understand the signal
+        let all_changes = get_signal_changes("simple.vcd".to_string(), signal_ref, 0, u64::MAX, None)
+            .expect("Failed to get all signal changes");
+        
+        assert!(all_changes.len() >= 4, "Expected at least 4 changes for clk signal");
+
+        // Now test with a range that excludes the first and last changes
+        // Choose a range like [30, 100] which should be in the middle
+        let range_start = 30;
+        let range_end = 100;
+
+        let range_changes = get_signal_changes("simple.vcd".to_string(), signal_ref, range_start, range_end, None)
+            .expect("Failed to get range signal changes");
+
+        // Verify that the first returned change is before the range start
+        assert!(
+            !range_changes.is_empty(),
+            "Expected at least one change in result"
+        );
+        
+        // The first change should be before or at the start (it's the boundary change)
+        assert!(
+            range_changes[0].time <= range_start,
+            "First change should be at or before range start. Got time={}, expected <= {}",
+            range_changes[0].time,
+            range_start
+        );
+
+        // At least one change should be within the range
+        let has_change_in_range = range_changes.iter().any(|c| c.time >= range_start && c.time <= range_end);
+        assert!(has_change_in_range, "Expected at least one change within the range");
+
+        // The last change should be after the range end (if there are changes after end)
+        // Find if there's a change after end in all_changes
+        let has_change_after_end = all_changes.iter().any(|c| c.time > range_end);
+        if has_change_after_end {
+            // Then our result should include it as the last element
+            assert!(
+                range_changes.last().unwrap().time > range_end,
+                "Last change should be after range end when such a change exists. Got time={}, expected > {}",
+                range_changes.last().unwrap().time,
+                range_end
+            );
+        }
+
+        // Cleanup
+        remove_file("simple.vcd".to_string());
+    }
+}
+
+fn scope_type_name(scope_type: wellen::ScopeType) -> &'static str {
+    use wellen::ScopeType;
+    match scope_type {
+        ScopeType::Module => "module",
+        ScopeType::Task => "task",
+        ScopeType::Function => "function",
+        ScopeType::Begin => "begin",
+        ScopeType::Fork => "fork",
+        ScopeType::Generate => "generate",
+        ScopeType::Struct => "struct",
+        ScopeType::Union => "union",
+        ScopeType::Class => "class",
+        ScopeType::Interface => "interface",
+        ScopeType::Package => "package",
+        ScopeType::Program => "program",
+        ScopeType::VhdlArchitecture => "vhdl_architecture",
+        ScopeType::VhdlProcedure => "vhdl_procedure",
+        ScopeType::VhdlFunction => "vhdl_function",
+        ScopeType::VhdlRecord => "vhdl_record",
+        ScopeType::VhdlProcess => "vhdl_process",
+        ScopeType::VhdlBlock => "vhdl_block",
+        ScopeType::VhdlForGenerate => "vhdl_for_generate",
+        ScopeType::VhdlIfGenerate => "vhdl_if_generate",
+        ScopeType::VhdlGenerate => "vhdl_generate",
+        ScopeType::VhdlPackage => "vhdl_package",
+        ScopeType::GhwGeneric => "ghw_generic",
+        ScopeType::VhdlArray => "vhdl_array",
+    }
+}
+
+fn summarize_scope(file: &LoadedWave, scope_ref: wellen::ScopeRef) -> ScopeSummary {
+    let hierarchy = file.wave.hierarchy();
+    let scope = &hierarchy[scope_ref];
+    let scope_count = scope.scopes(hierarchy).count();
+    let var_count = scope.vars(hierarchy).count() + virtual_var_indices(file, Some(scope_ref)).len();
+
+    ScopeSummary {
+        name: scope.name(hierarchy).to_string(),
+        ref_: scope_ref.index(),
+        scope_type: scope_type_name(scope.scope_type()).to_string(),
+        scope_count,
+        var_count,
+        has_children: scope_count + var_count > 0,
+    }
+}
+
+fn to_scope_ref(hierarchy: &wellen::Hierarchy, scope_ref: Option<usize>) -> Result<Option<wellen::ScopeRef>, String> {
+    match scope_ref {
+        None => Ok(None),
+        Some(index) if index < hierarchy.iter_scopes().len() => Ok(wellen::ScopeRef::from_index(index)),
+        Some(_) => Err("Invalid scope ID".to_string()),
+    }
+}
+
+/// Signals of a subtree scanned by `for_each_streamed` are loaded this many at a time.
+const STREAM_CHUNK: usize = 1024;
+
+/// Visits file signals a chunk at a time, loading each chunk and unloading afterwards the
+/// signals that were not loaded before, so that scanning a large subtree does not keep
+/// the whole dump in memory.
+fn for_each_streamed(
+    file: &mut LoadedWave,
+    signal_ids: &[usize],
+    mut visit: impl FnMut(&LoadedWave, usize) -> Result<(), String>,
+) -> Result<(), String> {
+    for chunk in signal_ids.chunks(STREAM_CHUNK) {
+        let fresh: Vec<wellen::SignalRef> = chunk
+            .iter()
+            .filter_map(|id| to_signal_ref(&file.wave, *id).ok())
+            .filter(|signal_ref| file.wave.get_signal(*signal_ref).is_none())
+            .collect();
+        load_signal_ids(file, chunk)?;
+        let visited = chunk.iter().try_for_each(|id| visit(file, *id));
+        file.wave.unload_signals(&fresh);
+        visited?;
+    }
+    Ok(())
+}
+
+/// Every var under a scope, at any depth, or of the whole file for `None`.
+fn subtree_vars(hierarchy: &wellen::Hierarchy, scope_ref: Option<wellen::ScopeRef>) -> Vec<wellen::VarRef> {
+    let (mut vars, mut pending): (Vec<_>, Vec<_>) = match scope_ref {
+        None => (hierarchy.vars().collect(), hierarchy.scopes().collect()),
+        Some(scope_ref) => (Vec::new(), vec![scope_ref]),
+    };
+    while let Some(scope_ref) = pending.pop() {
+        let scope = &hierarchy[scope_ref];
+        vars.extend(scope.vars(hierarchy));
+        pending.extend(scope.scopes(hierarchy));
+    }
+    vars
+}
+
+/// Lists one level of the hierarchy. `scope_ref` of `None` lists the top-level items.
+pub fn get_scope_children(
+    filename: String,
+    scope_ref: Option<usize>,
+    offset: usize,
+    limit: usize,
+) -> Result<ScopeChildren, String> {
+    let files = OPENED_FILES.lock().unwrap();
+    let file = find_file(&files, &filename)?;
+    let hierarchy = file.wave.hierarchy();
+
+    let scope_ref = to_scope_ref(hierarchy, scope_ref)?;
+    let (child_scopes, child_vars): (Vec<wellen::ScopeRef>, Vec<wellen::VarRef>) = match scope_ref {
+        None => (hierarchy.scopes().collect(), hierarchy.vars().collect()),
+        Some(scope_ref) => {
+            let scope = &hierarchy[scope_ref];
+            (scope.scopes(hierarchy).collect(), scope.vars(hierarchy).collect())
+        }
+    };
+
+    let scopes = child_scopes
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|child| summarize_scope(file, *child))
+        .collect::<Vec<_>>();
+
+    let virtual_vars = virtual_var_indices(file, scope_ref);
+    let total_vars = child_vars.len() + virtual_vars.len();
+
+    // Only the vars of the page are built: those of the file as `Ok`, virtual ones as `Err`
+    let vars = child_vars
+        .iter()
+        .map(Ok)
+        .chain(virtual_vars.iter().map(Err))
+        .skip(offset.saturating_sub(child_scopes.len()))
+        .take(limit - scopes.len())
+        .map(|child| match child {
+            Ok(var_ref) => build_var(file, *var_ref),
+            Err(index) => build_virtual_var(file, *index),
+        })
+        .collect();
+
+    Ok(ScopeChildren {
+        scopes,
+        vars,
+        total_scopes: child_scopes.len(),
+        total_vars,
+    })
+}
+
+#[wasm_bindgen]
+pub fn get_scope_children_wasm(
+    filename: String,
+    scope_ref: Option<usize>,
+    offset: usize,
+    limit: usize,
+) -> Result<JsValue, String> {
+    let children = get_scope_children(filename, scope_ref, offset, limit)?;
+    serde_wasm_bindgen::to_value(&children).map_err(|e| e.to_string())
+}
+
+pub fn search_signals(
+    filename: String,
+    query: SearchQuery,
+    offset: usize,
+    limit: usize,
+) -> Result<SearchResults, String> {
+    let files = OPENED_FILES.lock().unwrap();
+    let file = find_file(&files, &filename)?;
+    file.search_index.search(&query, offset, limit)
+}
+
+#[wasm_bindgen]
+pub fn search_signals_wasm(
+    filename: String,
+    query: JsValue,
+    offset: usize,
+    limit: usize,
+) -> Result<JsValue, String> {
+    let query: SearchQuery = serde_wasm_bindgen::from_value(query).map_err(|e| e.to_string())?;
+    let results = search_signals(filename, query, offset, limit)?;
+    serde_wasm_bindgen::to_value(&results).map_err(|e| e.to_string())
+}
+
+/// Serialises the whole design at once. Prefer `get_scope_children` for large
+/// hierarchies, which lets the UI expand scopes on demand.
+pub fn get_hierarchy(filename: String) -> Result<HierarchyRoot, String> {
+    let files = OPENED_FILES.lock().unwrap();
+    let file = find_file(&files, &filename)?;
+
+    let hierarchy = file.wave.hierarchy();
+
+    let root_vars = build_virtual_vars(file, None);
+    let mut root_scopes = Vec::new();
+
+    for scope_ref in hierarchy.scopes() {
+        root_scopes.push(build_scope(file, scope_ref));
+    }
+
+    // Creating a synthetic "root" to hold everything
+    Ok(HierarchyRoot {
+        name: "root".to_string(),
+        ref_: 0, // Placeholder
+        vars: root_vars,
+        scopes: root_scopes,
+    })
+}
+
+#[wasm_bindgen]
+pub fn get_hierarchy_wasm(filename: String) -> Result<JsValue, String> {
+    let root = get_hierarchy(filename)?;
+    serde_wasm_bindgen::to_value(&root).map_err(|e| e.to_string())
+}
+
+fn timescale_unit_name(unit: wellen::TimescaleUnit) -> &'static str {
+    match unit {
+        wellen::TimescaleUnit::FemtoSeconds => "fs",
+        wellen::TimescaleUnit::PicoSeconds => "ps",
+        wellen::TimescaleUnit::NanoSeconds => "ns",
+        wellen::TimescaleUnit::MicroSeconds => "us",
+        wellen::TimescaleUnit::MilliSeconds => "ms",
+        wellen::TimescaleUnit::Seconds => "s",
+        wellen::TimescaleUnit::Unknown => "unknown",
+    }
+}
+
+fn file_format_name(format: wellen::FileFormat) -> &'static str {
+    match format {
+        wellen::FileFormat::Vcd => "VCD",
+        wellen::FileFormat::Fst => "FST",
+        wellen::FileFormat::Ghw => "GHW",
+        wellen::FileFormat::Unknown => "Unknown",
+    }
+}
+
+pub fn get_waveform_info(filename: String) -> Result<WaveformInfo, String> {
+    let files = OPENED_FILES.lock().unwrap();
+    let file = find_file(&files, &filename)?;
+
+    let waveform = &file.wave;
+    let hierarchy = waveform.hierarchy();
+    let time_table = waveform.time_table();
+
+    let timescale = hierarchy.timescale().map(|ts| TimescaleInfo {
+        factor: ts.factor,
+        unit: timescale_unit_name(ts.unit).to_string(),
+        exponent: ts.unit.to_exponent(),
+    });
+
+    Ok(WaveformInfo {
+        timescale,
+        start_time: time_table.first().copied(),
+        end_time: time_table.last().copied(),
+        file_format: file_format_name(hierarchy.file_format()).to_string(),
+        date: hierarchy.date().trim().to_string(),
+        version: hierarchy.version().trim().to_string(),
+        var_count: hierarchy.iter_vars().len(),
+        scope_count: hierarchy.iter_scopes().len(),
+        signal_count: hierarchy.num_unique_signals(),
+    })
+}
+
+#[wasm_bindgen]
+pub fn get_waveform_info_wasm(filename: String) -> Result<JsValue, String> {
+    let info = get_waveform_info(filename)?;
+    serde_wasm_bindgen::to_value(&info).map_err(|e| e.to_string())
+}
+
+use wellen::SignalRef;
+
+fn to_signal_ref(waveform: &wellen::simple::Waveform, signal_id: usize) -> Result<SignalRef, String> {
+    if signal_id >= waveform.hierarchy().num_unique_signals() {
+        return Err("Invalid signal ID".to_string());
+    }
+    SignalRef::from_index(signal_id).ok_or_else(|| "Invalid signal ID".to_string())
+}
+
+/// Loads the signal data if not already loaded (required for lazy-loaded backends).
+/// Native builds decode several signals in parallel; wasm has no threads to spare.
+fn load_signals(waveform: &mut wellen::simple::Waveform, signal_refs: &[SignalRef]) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if signal_refs.len() > 1 {
+        waveform.load_signals_multi_threaded(signal_refs);
+        return;
+    }
+    waveform.load_signals(signal_refs);
+}
+
+fn virtual_signal(file: &LoadedWave, signal_id: usize) -> Result<&virtual_signal::VirtualSignal, String> {
+    signal_id
+        .checked_sub(file.wave.hierarchy().num_unique_signals())
+        .and_then(|index| file.virtual_signals.get(index))
+        .and_then(Option::as_ref)
+        .ok_or_else(|| "Invalid signal ID".to_string())
+}
+
+/// Loads the file signals among `signal_ids`, checks that the others are virtual signals
+/// and evaluates those not evaluated yet.
+fn load_signal_ids(file: &mut LoadedWave, signal_ids: &[usize]) -> Result<(), String> {
+    let mut signal_refs = Vec::new();
+    let mut unevaluated = Vec::new();
+    for signal_id in signal_ids {
+        if *signal_id < file.wave.hierarchy().num_unique_signals() {
+            signal_refs.push(to_signal_ref(&file.wave, *signal_id)?);
+        } else if !virtual_signal(file, *signal_id)?.is_evaluated() {
+            unevaluated.push(*signal_id);
+        }
+    }
+    load_signals(&mut file.wave, &signal_refs);
+    for signal_id in unevaluated {
+        evaluate_virtual_signal(file, signal_id)?;
+    }
+    Ok(())
+}
+
+fn evaluate_virtual_signal(file: &mut LoadedWave, signal_id: usize) -> Result<(), String> {
+    let parsed = parse_unclocked(file, &virtual_signal(file, signal_id)?.expression)?;
+    load_signal_ids(file, parsed.operands())?;
+    let sources = parsed
+        .operands()
+        .iter()
+        .map(|id| signal_data(file, *id).map(|(signal, _)| signal))
+        .collect::<Result<Vec<_>, _>>()?;
+    let changes = virtual_signal::Changes::evaluate(&parsed, &sources);
+
+    let index = signal_id - file.wave.hierarchy().num_unique_signals();
+    if let Some(signal) = file.virtual_signals[index].as_mut() {
+        signal.set_changes(changes);
+    }
+    Ok(())
+}
+
+/// Changes and encoding of a file or virtual signal. File signals must be loaded first.
+fn signal_data(file: &LoadedWave, signal_id: usize) -> Result<(changes::SignalData<'_>, wellen::SignalEncoding), String> {
+    if signal_id < file.wave.hierarchy().num_unique_signals() {
+        let signal_ref = to_signal_ref(&file.wave, signal_id)?;
+        let signal = file.wave.get_signal(signal_ref).ok_or("Signal not found")?;
+        let encoding = file.wave.hierarchy().get_signal_tpe(signal_ref).ok_or("Signal not found")?;
+        Ok((changes::SignalData::Loaded(signal), encoding))
     } else {
-        Ok(result)
+        let signal = virtual_signal(file, signal_id)?;
+        Ok((changes::SignalData::Virtual(signal), signal.encoding()))
+    }
+}
+
+/// Values are rendered with `format`, raw bit strings when `None`.
+pub fn get_signal_changes(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    format: Option<ValueFormat>,
+) -> Result<Vec<SignalChange>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, _) = signal_data(file, signal_id)?;
+    let render = value_renderer(file, signal_id, format.unwrap_or_default());
+    let mut changes = collect_signal_changes(signal, file.wave.time_table(), start, end, &render)?;
+    let translator = translator_of(file, signal_id);
+    drop(render);
+    drop(files);
+
+    translate_values(translator.as_ref(), changes.iter_mut().map(|change| &mut change.value));
+    Ok(changes)
+}
+
+/// Fetches the changes of many signals over the same window, loading them all at once.
+pub fn get_signal_changes_batch(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: u64,
+    format: Option<ValueFormat>,
+) -> Result<BTreeMap<usize, Vec<SignalChange>>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &signal_ids)?;
+
+    let mut result = BTreeMap::new();
+    let mut translators = Vec::new();
+    for signal_id in &signal_ids {
+        let (signal, _) = signal_data(file, *signal_id)?;
+        let render = value_renderer(file, *signal_id, format.unwrap_or_default());
+        let changes = collect_signal_changes(signal, file.wave.time_table(), start, end, &render)?;
+        result.insert(*signal_id, changes);
+        translators.push((*signal_id, translator_of(file, *signal_id)));
+    }
+    drop(files);
+
+    for (signal_id, translator) in translators {
+        let changes = result.get_mut(&signal_id).into_iter().flatten();
+        translate_values(translator.as_ref(), changes.map(|change| &mut change.value));
+    }
+    Ok(result)
+}
+
+/// Summarises a signal over `buckets` equal time spans of `[start, end)`, typically one
+/// per pixel column, instead of returning every change in the window.
+pub fn get_signal_summary(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    buckets: usize,
+) -> Result<Vec<SummaryBucket>, String> {
+    if end <= start {
+        return Err(format!("Invalid time range: start ({}) must be less than end ({})", start, end));
+    }
+    if buckets == 0 {
+        return Err("Bucket count must be positive".to_string());
+    }
+
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, encoding) = signal_data(file, signal_id)?;
+    if encoding != wellen::SignalEncoding::String && !file.min_max_pyramids.contains_key(&signal_id) {
+        let pyramid = summary::MinMaxPyramid::build(signal);
+        file.min_max_pyramids.insert(signal_id, pyramid);
+    }
+
+    let (signal, _) = signal_data(file, signal_id)?;
+    let pyramid = file.min_max_pyramids.get(&signal_id);
+    Ok(summary::summarize(signal, file.wave.time_table(), pyramid, start, end, buckets))
+}
+
+#[wasm_bindgen]
+pub fn get_signal_summary_wasm(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    buckets: usize,
+) -> Result<JsValue, String> {
+    let summary = get_signal_summary(filename, signal_id, start, end, buckets)?;
+    serde_wasm_bindgen::to_value(&summary).map_err(|e| e.to_string())
+}
+
+/// Analyses the given signals as clocks, reusing earlier results. They must be loaded.
+fn analyze_clocks(file: &mut LoadedWave, signal_ids: &[usize]) -> Result<(), String> {
+    let time_table = file.wave.time_table();
+    let end_time = time_table.last().copied().unwrap_or_default();
+    let seconds_per_unit = file
+        .wave
+        .hierarchy()
+        .timescale()
+        .and_then(|ts| Some(ts.factor as f64 * 10f64.powi(ts.unit.to_exponent()? as i32)));
+
+    let mut analysed = Vec::new();
+    for signal_id in signal_ids.iter().filter(|id| !file.clocks.contains_key(id)) {
+        let (signal, encoding) = signal_data(file, *signal_id)?;
+        let one_bit = matches!(encoding, wellen::SignalEncoding::BitVector(width) if width.get() == 1);
+        let info = match one_bit {
+            true => clock::analyze(*signal_id, signal_path(file, *signal_id)?, signal, time_table, end_time, seconds_per_unit),
+            false => None,
+        };
+        analysed.push((*signal_id, info));
+    }
+    file.clocks.extend(analysed);
+    Ok(())
+}
+
+/// Finds the clock-like signals of the file: 1-bit signals rising at a regular period with
+/// a regular duty cycle. Loads every 1-bit signal of the file the first time.
+pub fn detect_clocks(filename: String) -> Result<Vec<ClockInfo>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    let hierarchy = file.wave.hierarchy();
+    let signal_ids: Vec<usize> = (0..hierarchy.num_unique_signals())
+        .filter(|id| {
+            let encoding = to_signal_ref(&file.wave, *id).ok().and_then(|signal_ref| hierarchy.get_signal_tpe(signal_ref));
+            matches!(encoding, Some(wellen::SignalEncoding::BitVector(width)) if width.get() == 1)
+        })
+        .collect();
+    load_signal_ids(file, &signal_ids)?;
+    analyze_clocks(file, &signal_ids)?;
+    Ok(signal_ids.iter().filter_map(|id| file.clocks[id].clone()).collect())
+}
+
+#[wasm_bindgen]
+pub fn detect_clocks_wasm(filename: String) -> Result<JsValue, String> {
+    let clocks = detect_clocks(filename)?;
+    serde_wasm_bindgen::to_value(&clocks).map_err(|e| e.to_string())
+}
+
+/// Period, frequency, duty cycle, jitter and gated intervals of one signal, or `None` when
+/// it does not behave like a clock.
+pub fn get_clock_info(filename: String, signal_id: usize) -> Result<Option<ClockInfo>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+    analyze_clocks(file, &[signal_id])?;
+    Ok(file.clocks[&signal_id].clone())
+}
+
+#[wasm_bindgen]
+pub fn get_clock_info_wasm(filename: String, signal_id: usize) -> Result<JsValue, String> {
+    let info = get_clock_info(filename, signal_id)?;
+    serde_wasm_bindgen::to_value(&info).map_err(|e| e.to_string())
+}
+
+/// The cycle axis of a clock, built on first use.
+fn cycle_axis(file: &mut LoadedWave, clock_id: usize, edge: ClockEdge) -> Result<&cycles::CycleAxis, String> {
+    if !file.cycle_axes.contains_key(&(clock_id, edge)) {
+        load_signal_ids(file, &[clock_id])?;
+        let (clock, _) = signal_data(file, clock_id)?;
+        let axis = cycles::CycleAxis::build(clock, file.wave.time_table(), edge)?;
+        file.cycle_axes.insert((clock_id, edge), axis);
     }
+    Ok(&file.cycle_axes[&(clock_id, edge)])
+}
+
+/// Sets up a time axis counted in cycles of `clock`, each starting at one of its `edge`s.
+pub fn get_cycle_axis(filename: String, clock_id: usize, edge: ClockEdge) -> Result<CycleAxisInfo, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+    Ok(cycle_axis(file, clock_id, edge)?.info())
+}
+
+#[wasm_bindgen]
+pub fn get_cycle_axis_wasm(filename: String, clock_id: usize, edge: JsValue) -> Result<JsValue, String> {
+    let edge: ClockEdge = serde_wasm_bindgen::from_value(edge).map_err(|e| e.to_string())?;
+    let info = get_cycle_axis(filename, clock_id, edge)?;
+    serde_wasm_bindgen::to_value(&info).map_err(|e| e.to_string())
+}
+
+/// Converts times, such as navigation results or search matches, to fractional cycles.
+pub fn times_to_cycles(filename: String, clock_id: usize, edge: ClockEdge, times: Vec<u64>) -> Result<Vec<f64>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+    let axis = cycle_axis(file, clock_id, edge)?;
+    Ok(times.into_iter().map(|time| axis.cycle_at(time)).collect())
+}
+
+#[wasm_bindgen]
+pub fn times_to_cycles_wasm(filename: String, clock_id: usize, edge: JsValue, times: Vec<u64>) -> Result<Vec<f64>, String> {
+    let edge: ClockEdge = serde_wasm_bindgen::from_value(edge).map_err(|e| e.to_string())?;
+    times_to_cycles(filename, clock_id, edge, times)
+}
+
+/// Converts cycle positions to times, for example the two ends of a cycle range to the
+/// window to pass to `get_signal_changes`.
+pub fn cycles_to_times(filename: String, clock_id: usize, edge: ClockEdge, cycles: Vec<f64>) -> Result<Vec<u64>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+    let axis = cycle_axis(file, clock_id, edge)?;
+    Ok(cycles.into_iter().map(|cycle| axis.time_at(cycle)).collect())
+}
+
+#[wasm_bindgen]
+pub fn cycles_to_times_wasm(filename: String, clock_id: usize, edge: JsValue, cycles: Vec<f64>) -> Result<Vec<u64>, String> {
+    let edge: ClockEdge = serde_wasm_bindgen::from_value(edge).map_err(|e| e.to_string())?;
+    cycles_to_times(filename, clock_id, edge, cycles)
+}
+
+/// Samples signals at every edge of a clock in a window, one row per edge, a page at a
+/// time. Values are rendered like those of `get_signal_changes`.
+pub fn sample_on_clock(filename: String, query: SampleQuery) -> Result<SampledTable, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &query.signal_ids)?;
+    let edges = cycle_axis(file, query.clock_id, query.edge)?.edges();
+    let first = edges.partition_point(|time| *time < query.start);
+    let last = query.end.map_or(edges.len(), |end| edges.partition_point(|time| *time <= end)).max(first);
+    let page_start = first.saturating_add(query.offset).min(last);
+    let page_end = page_start.saturating_add(query.limit).min(last);
+    let page: Vec<(usize, u64)> = (page_start..page_end).map(|cycle| (cycle, edges[cycle])).collect();
+
+    let format = query.format.unwrap_or_default();
+    let columns = query
+        .signal_ids
+        .iter()
+        .map(|id| Ok((signal_data(file, *id)?.0, value_renderer(file, *id, format))))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut rows = sampling::sample_rows(&page, &columns, file.wave.time_table())?;
+    let translators: Vec<_> = query.signal_ids.iter().map(|id| translator_of(file, *id)).collect();
+    drop(columns);
+    drop(files);
+
+    for (column, translator) in translators.iter().enumerate() {
+        translate_values(translator.as_ref(), rows.iter_mut().filter_map(|row| row.values[column].as_mut()));
+    }
+    Ok(SampledTable { rows, total_rows: last - first })
+}
+
+#[wasm_bindgen]
+pub fn sample_on_clock_wasm(filename: String, query: JsValue) -> Result<JsValue, String> {
+    let query: SampleQuery = serde_wasm_bindgen::from_value(query).map_err(|e| e.to_string())?;
+    let table = sample_on_clock(filename, query)?;
+    serde_wasm_bindgen::to_value(&table).map_err(|e| e.to_string())
+}
+
+/// Lists every time at which any of the signals changes, with the values of all of them,
+/// a page at a time. Values are rendered like those of `get_signal_changes`.
+pub fn get_event_list(filename: String, query: EventListQuery) -> Result<EventListPage, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &query.signal_ids)?;
+    let format = query.format.unwrap_or_default();
+    let columns = query
+        .signal_ids
+        .iter()
+        .map(|id| Ok((signal_data(file, *id)?.0, value_renderer(file, *id, format))))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut page = event_list::page(&columns, file.wave.time_table(), &query)?;
+    let translators: Vec<_> = query.signal_ids.iter().map(|id| translator_of(file, *id)).collect();
+    drop(columns);
+    drop(files);
+
+    for (column, translator) in translators.iter().enumerate() {
+        translate_values(translator.as_ref(), page.rows.iter_mut().filter_map(|row| row.values[column].as_mut()));
+    }
+    Ok(page)
+}
+
+#[wasm_bindgen]
+pub fn get_event_list_wasm(filename: String, query: JsValue) -> Result<JsValue, String> {
+    let query: EventListQuery = serde_wasm_bindgen::from_value(query).map_err(|e| e.to_string())?;
+    let page = get_event_list(filename, query)?;
+    serde_wasm_bindgen::to_value(&page).map_err(|e| e.to_string())
+}
+
+/// Exports the list of `[start, end]` as CSV, with a `time` column and one column per
+/// signal named by its path.
+pub fn export_event_list_csv(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: Option<u64>,
+    format: Option<ValueFormat>,
+) -> Result<String, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &signal_ids)?;
+    let format = format.unwrap_or_default();
+    let names = signal_ids.iter().map(|id| signal_path(file, *id)).collect::<Result<Vec<_>, String>>()?;
+    let columns = signal_ids
+        .iter()
+        .map(|id| Ok((signal_data(file, *id)?.0, value_renderer(file, *id, format))))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut rows = event_list::rows_between(&columns, file.wave.time_table(), start, end)?;
+    let translators: Vec<_> = signal_ids.iter().map(|id| translator_of(file, *id)).collect();
+    drop(columns);
+    drop(files);
+
+    for (column, translator) in translators.iter().enumerate() {
+        translate_values(translator.as_ref(), rows.iter_mut().filter_map(|row| row.values[column].as_mut()));
+    }
+    Ok(event_list::to_csv(&names, &rows))
+}
+
+#[wasm_bindgen]
+pub fn export_event_list_csv_wasm(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: Option<u64>,
+    format: JsValue,
+) -> Result<String, String> {
+    export_event_list_csv(filename, signal_ids, start, end, from_optional_js(format)?)
+}
+
+/// Finds the vars under a scope, or in the whole file for `None`, whose signal has a
+/// transition within `radius` of `time`, closest first. Returns at most `limit` of them,
+/// each with its transition closest to `time`. Signals not loaded yet are loaded a chunk
+/// at a time and unloaded again.
+pub fn get_changes_near(
+    filename: String,
+    scope_ref: Option<usize>,
+    time: u64,
+    radius: u64,
+    limit: usize,
+    format: Option<ValueFormat>,
+) -> Result<NearbyChanges, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    // The vars of each signal, aliases included
+    let hierarchy = file.wave.hierarchy();
+    let mut vars: BTreeMap<usize, Vec<wellen::VarRef>> = BTreeMap::new();
+    for var_ref in subtree_vars(hierarchy, to_scope_ref(hierarchy, scope_ref)?) {
+        vars.entry(hierarchy[var_ref].signal_ref().index()).or_default().push(var_ref);
+    }
+    let signal_ids: Vec<usize> = vars.keys().copied().collect();
+
+    let format = format.unwrap_or_default();
+    let mut found = Vec::new();
+    for_each_streamed(file, &signal_ids, |file, signal_id| {
+        let (signal, _) = signal_data(file, signal_id)?;
+        let Some((index, change_time, transitions)) = nearby::closest_transition(signal, file.wave.time_table(), time, radius) else {
+            return Ok(());
+        };
+        // Rendered now, as the signal is unloaded once its chunk is done
+        let render = value_renderer(file, signal_id, format);
+        let value = |index: usize| changes::value_at(signal, index).map(|value| render(&value)).transpose();
+        let old_value = index.checked_sub(1).map(value).transpose()?.flatten();
+        let new_value = value(index)?.ok_or("Signal not found")?;
+        for var_ref in &vars[&signal_id] {
+            let path = bus::var_path(file.wave.hierarchy(), *var_ref);
+            let change = NearbyChange {
+                signal_id,
+                path,
+                time: change_time,
+                distance: change_time.abs_diff(time),
+                old_value: old_value.clone(),
+                new_value: new_value.clone(),
+                transitions,
+            };
+            found.push(change);
+        }
+        Ok(())
+    })?;
+    found.sort_unstable_by(|a, b| (a.distance, a.time, &a.path).cmp(&(b.distance, b.time, &b.path)));
+
+    let total = found.len();
+    found.truncate(limit);
+    let changes = found;
+    let mut changes: Vec<_> = changes.into_iter().map(|change| (translator_of(file, change.signal_id), change)).collect();
+    drop(files);
+
+    for (translator, change) in &mut changes {
+        translate_values(translator.as_ref(), change.old_value.iter_mut().chain([&mut change.new_value]));
+    }
+    Ok(NearbyChanges { changes: changes.into_iter().map(|(_, change)| change).collect(), total })
+}
+
+#[wasm_bindgen]
+pub fn get_changes_near_wasm(
+    filename: String,
+    scope_ref: Option<usize>,
+    time: u64,
+    radius: u64,
+    limit: usize,
+    format: JsValue,
+) -> Result<JsValue, String> {
+    let changes = get_changes_near(filename, scope_ref, time, radius, limit, from_optional_js(format)?)?;
+    serde_wasm_bindgen::to_value(&changes).map_err(|e| e.to_string())
+}
+
+/// Counts the transitions of a signal, or of every signal under a scope, in `buckets` equal
+/// spans of the whole trace, showing where in time and in the design activity happens.
+/// There are at most as many buckets as times in the trace, and no more than
+/// `activity::MAX_BUCKETS`. The latest result of each source is kept. A scope loads the
+/// signals under it a chunk at a time, unloading those that were not loaded before.
+pub fn get_activity_map(filename: String, source: ActivitySource, buckets: usize) -> Result<ActivityMap, String> {
+    if buckets == 0 {
+        return Err("Bucket count must be positive".to_string());
+    }
+
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+    let buckets = buckets.min(activity::MAX_BUCKETS).min(file.wave.time_table().len().max(1));
+    if let Some(map) = file.activity_maps.get(&source).filter(|map| map.transitions.len() == buckets) {
+        return Ok(map.clone());
+    }
+
+    let end = file.wave.time_table().last().copied().unwrap_or_default();
+    let mut map = ActivityMap::new(end, buckets);
+    match source {
+        ActivitySource::Signal { signal_id } => {
+            load_signal_ids(file, &[signal_id])?;
+            map.add(signal_data(file, signal_id)?.0, file.wave.time_table());
+        }
+        ActivitySource::Scope { scope_ref } => {
+            let hierarchy = file.wave.hierarchy();
+            let vars = subtree_vars(hierarchy, to_scope_ref(hierarchy, scope_ref)?);
+            let mut signal_ids: Vec<usize> = vars.iter().map(|var_ref| hierarchy[*var_ref].signal_ref().index()).collect();
+            signal_ids.sort_unstable();
+            signal_ids.dedup();
+            for_each_streamed(file, &signal_ids, |file, signal_id| {
+                map.add(signal_data(file, signal_id)?.0, file.wave.time_table());
+                Ok(())
+            })?;
+        }
+    }
+    file.activity_maps.insert(source, map.clone());
+    Ok(map)
+}
+
+#[wasm_bindgen]
+pub fn get_activity_map_wasm(filename: String, source: JsValue, buckets: usize) -> Result<JsValue, String> {
+    let source = serde_wasm_bindgen::from_value(source).map_err(|e| e.to_string())?;
+    let map = get_activity_map(filename, source, buckets)?;
+    serde_wasm_bindgen::to_value(&map).map_err(|e| e.to_string())
+}
+
+/// Counts transitions, time at each level, value range and more for one signal over
+/// `[start, end)`, as used to sanity-check clocks and enables.
+pub fn get_signal_statistics(filename: String, signal_id: usize, start: u64, end: u64) -> Result<SignalStatistics, String> {
+    if end <= start {
+        return Err(format!("Invalid time range: start ({}) must be less than end ({})", start, end));
+    }
+
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, encoding) = signal_data(file, signal_id)?;
+    Ok(statistics::statistics(signal, encoding, file.wave.time_table(), start, end))
+}
+
+#[wasm_bindgen]
+pub fn get_signal_statistics_wasm(filename: String, signal_id: usize, start: u64, end: u64) -> Result<JsValue, String> {
+    let statistics = get_signal_statistics(filename, signal_id, start, end)?;
+    serde_wasm_bindgen::to_value(&statistics).map_err(|e| e.to_string())
+}
+
+/// Reads a real signal, or a bit vector as an unsigned number, as numbers for plotting,
+/// along with the range of the window's values. With `max_samples`, a window holding more
+/// changes keeps only the lowest and highest samples of each of `max_samples / 2` spans.
+pub fn get_analog_samples(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    max_samples: Option<usize>,
+) -> Result<AnalogWindow, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, encoding) = signal_data(file, signal_id)?;
+    if encoding == wellen::SignalEncoding::String {
+        return Err("String signals have no numeric values".to_string());
+    }
+    let interpolation = file.interpolations.get(&signal_id).copied().unwrap_or_default();
+    Ok(analog::analog_window(signal, file.wave.time_table(), start, end, max_samples, interpolation))
+}
+
+#[wasm_bindgen]
+pub fn get_analog_samples_wasm(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    max_samples: Option<usize>,
+) -> Result<JsValue, String> {
+    let window = get_analog_samples(filename, signal_id, start, end, max_samples)?;
+    serde_wasm_bindgen::to_value(&window).map_err(|e| e.to_string())
+}
+
+/// Sets how analog views draw a signal between its samples.
+pub fn set_interpolation(filename: String, signal_id: usize, interpolation: Interpolation) -> Result<(), String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    if to_signal_ref(&file.wave, signal_id).is_err() {
+        virtual_signal(file, signal_id)?;
+    }
+    match interpolation {
+        Interpolation::Step => file.interpolations.remove(&signal_id),
+        _ => file.interpolations.insert(signal_id, interpolation),
+    };
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn set_interpolation_wasm(filename: String, signal_id: usize, interpolation: JsValue) -> Result<(), String> {
+    let interpolation: Interpolation = serde_wasm_bindgen::from_value(interpolation).map_err(|e| e.to_string())?;
+    set_interpolation(filename, signal_id, interpolation)
+}
+
+/// Renders the values of a signal with `format`, labelled by its translation table where
+/// the table lists them. Signals with a translator are left formatted, for `translate_values`
+/// to pass through it.
+fn value_renderer(
+    file: &LoadedWave,
+    signal_id: usize,
+    format: ValueFormat,
+) -> impl Fn(&wellen::SignalValue) -> Result<String, String> + '_ {
+    let table = file
+        .signal_translations
+        .get(&signal_id)
+        .and_then(|name| file.translation_tables.get(name))
+        .filter(|_| !file.signal_translators.contains_key(&signal_id));
+    move |value| {
+        let formatted = format::format_value(value, format);
+        Ok(table.and_then(|table| table.translate(value)).map_or(formatted, str::to_string))
+    }
+}
+
+fn translator_of(file: &LoadedWave, signal_id: usize) -> Option<SharedTranslator> {
+    file.signal_translators.get(&signal_id).cloned()
+}
+
+/// Passes rendered values through a signal's translator, if it has one. Call it once
+/// `OPENED_FILES` is released. Values the translator fails on stay formatted, so that a
+/// translator that stopped answering does not fail the whole query.
+fn translate_values<'a>(translator: Option<&SharedTranslator>, values: impl IntoIterator<Item = &'a mut String>) {
+    if let Some(translator) = translator {
+        let mut translator = translator.lock().unwrap();
+        for value in values {
+            if let Ok(label) = translator.translate(value) {
+                *value = label;
+            }
+        }
+    }
+}
+
+fn collect_signal_changes(
+    signal: changes::SignalData,
+    time_table: &[wellen::Time],
+    start: u64,
+    end: u64,
+    render: &dyn Fn(&wellen::SignalValue) -> Result<String, String>,
+) -> Result<Vec<SignalChange>, String> {
+    let range = changes::window_range(signal, time_table, start, end);
+
+    changes::iter_range(signal, range)
+        .map(|(time_idx, value)| {
+            Ok(SignalChange {
+                time: time_table[time_idx as usize],
+                value: render(&value)?,
+            })
+        })
+        .collect()
+}
+
+/// Reads an optional serde argument passed from JS, where `undefined` and `null` mean `None`.
+fn from_optional_js<T: serde::de::DeserializeOwned>(value: JsValue) -> Result<Option<T>, String> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+    serde_wasm_bindgen::from_value(value).map(Some).map_err(|e| e.to_string())
 }
 
 #[wasm_bindgen]
-pub fn get_signal_changes_wasm(filename: String, signal_id: usize, start: u64, end: u64) -> Result<JsValue, String> {
-    let changes = get_signal_changes(filename, signal_id, start, end)?;
+pub fn get_signal_changes_wasm(filename: String, signal_id: usize, start: u64, end: u64, format: JsValue) -> Result<JsValue, String> {
+    let changes = get_signal_changes(filename, signal_id, start, end, from_optional_js(format)?)?;
     serde_wasm_bindgen::to_value(&changes).map_err(|e| e.to_string())
 }
+
+/// Reads the value of each signal at `time`, along with the times of the changes around it.
+pub fn get_values_at(
+    filename: String,
+    signal_ids: Vec<usize>,
+    time: u64,
+    format: Option<ValueFormat>,
+) -> Result<BTreeMap<usize, ValueAt>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &signal_ids)?;
+
+    let time_table = file.wave.time_table();
+    let mut result = BTreeMap::new();
+    for signal_id in &signal_ids {
+        let (signal, _) = signal_data(file, *signal_id)?;
+        let time_indices = signal.time_indices();
+        let next = changes::first_change_after(time_indices, time_table, time);
+        let time_of = |index: usize| time_table[time_indices[index] as usize];
+
+        let current = next.checked_sub(1);
+        let render = value_renderer(file, *signal_id, format.unwrap_or_default());
+        result.insert(*signal_id, ValueAt {
+            value: current.and_then(|index| changes::value_at(signal, index)).map(|value| render(&value)).transpose()?,
+            previous_change: current.map(time_of),
+            next_change: (next < time_indices.len()).then(|| time_of(next)),
+        });
+    }
+    let translators: Vec<_> = signal_ids.iter().map(|id| (*id, translator_of(file, *id))).collect();
+    drop(files);
+
+    for (signal_id, translator) in translators {
+        let value = result.get_mut(&signal_id).and_then(|value_at| value_at.value.as_mut());
+        translate_values(translator.as_ref(), value);
+    }
+    Ok(result)
+}
+
+#[wasm_bindgen]
+pub fn get_values_at_wasm(filename: String, signal_ids: Vec<usize>, time: u64, format: JsValue) -> Result<JsValue, String> {
+    let values = get_values_at(filename, signal_ids, time, from_optional_js(format)?)?;
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    values.serialize(&serializer).map_err(|e| e.to_string())
+}
+
+/// Finds the next change after `from`, or the previous one before it, that matches
+/// `target`. Returns its time, or `None` when the signal has no such change.
+pub fn find_signal_change(
+    filename: String,
+    signal_id: usize,
+    from: u64,
+    direction: NavigationDirection,
+    target: NavigationTarget,
+) -> Result<Option<u64>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, encoding) = signal_data(file, signal_id)?;
+    navigation::find(signal, file.wave.time_table(), encoding, from, direction, &target)
+}
+
+#[wasm_bindgen]
+pub fn find_signal_change_wasm(
+    filename: String,
+    signal_id: usize,
+    from: u64,
+    direction: JsValue,
+    target: JsValue,
+) -> Result<JsValue, String> {
+    let direction = serde_wasm_bindgen::from_value(direction).map_err(|e| e.to_string())?;
+    let target = serde_wasm_bindgen::from_value(target).map_err(|e| e.to_string())?;
+    let time = find_signal_change(filename, signal_id, from, direction, target)?;
+    serde_wasm_bindgen::to_value(&time).map_err(|e| e.to_string())
+}
+
+/// How expressions refer to a virtual signal: by name at the top level, by the path of
+/// their scope and name otherwise.
+fn virtual_signal_path(file: &LoadedWave, signal: &virtual_signal::VirtualSignal) -> String {
+    let hierarchy = file.wave.hierarchy();
+    match signal.scope {
+        Some(scope_ref) => format!("{}.{}", hierarchy[scope_ref].full_name(hierarchy), signal.name),
+        None => signal.name.clone(),
+    }
+}
+
+/// How expressions refer to a file or virtual signal.
+fn signal_path(file: &LoadedWave, signal_id: usize) -> Result<String, String> {
+    if signal_id < file.wave.hierarchy().num_unique_signals() {
+        let var_ref = file.canonical_vars[signal_id].ok_or("Signal not found")?;
+        Ok(bus::var_path(file.wave.hierarchy(), var_ref))
+    } else {
+        Ok(virtual_signal_path(file, virtual_signal(file, signal_id)?))
+    }
+}
+
+/// Resolves a signal path from an expression. Virtual signals are found by path, file
+/// vars by their dotted hierarchical path and, for split buses, their index.
+fn resolve_signal(
+    file: &LoadedWave,
+    path: &str,
+    index: Option<(i64, i64)>,
+) -> Result<expression::ResolvedSignal, String> {
+    let hierarchy = file.wave.hierarchy();
+    if index.is_none() {
+        let existing = file.virtual_signals.iter().enumerate().find_map(|(i, signal)| {
+            signal.as_ref().filter(|signal| virtual_signal_path(file, signal) == path).map(|signal| (i, signal))
+        });
+        if let Some((i, signal)) = existing {
+            return Ok(expression::ResolvedSignal {
+                signal_id: hierarchy.num_unique_signals() + i,
+                width: signal.width() as usize,
+                index: signal.index,
+            });
+        }
+    }
+
+    let parts: Vec<&str> = path.split('.').collect();
+    let (name, scopes) = parts.split_last().ok_or("Empty signal path")?;
+    let var_index = index.map(|(msb, lsb)| wellen::VarIndex::new(msb, lsb));
+    let var_ref = hierarchy
+        .lookup_var_with_index(scopes, name, &var_index)
+        .ok_or_else(|| format!("Unknown signal: {}", path))?;
+    let var = &hierarchy[var_ref];
+    match var.signal_encoding() {
+        wellen::SignalEncoding::BitVector(width) => Ok(expression::ResolvedSignal {
+            signal_id: var.signal_ref().index(),
+            width: width.get() as usize,
+            index: var.index().map(|index| (index.msb(), index.lsb())),
+        }),
+        _ => Err(format!("{} is not a bit vector", path)),
+    }
+}
+
+/// Parses an expression evaluated at every change of its operands, where there is no
+/// previous sample for `$rose` and the like to look at.
+fn parse_unclocked(file: &LoadedWave, text: &str) -> Result<expression::Expression, String> {
+    let parsed = expression::Expression::parse(text, |path, index| resolve_signal(file, path, index))?;
+    if parsed.is_sampled() {
+        return Err("$rose, $fell, $stable and $past need a sampling clock and only work in assertions".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Evaluates `expression` over the file's signals and adds the result as a virtual signal
+/// named `name`. It can then be queried like any other signal through the returned var's id.
+/// See the `expression` module for the syntax.
+pub fn add_virtual_signal(filename: String, name: String, expression: String) -> Result<HierarchyVar, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+    add_virtual(file, name, expression)
+}
+
+fn add_virtual(file: &mut LoadedWave, name: String, expression: String) -> Result<HierarchyVar, String> {
+    if name.trim().is_empty() {
+        return Err("Virtual signal name must not be empty".to_string());
+    }
+    if file.virtual_signals.iter().flatten().any(|signal| signal.scope.is_none() && signal.name == name) {
+        return Err(format!("A virtual signal named {} already exists", name));
+    }
+
+    let parsed = parse_unclocked(file, &expression)?;
+    file.virtual_signals.push(Some(virtual_signal::VirtualSignal::new(name, expression, parsed.width())));
+    let id = file.wave.hierarchy().num_unique_signals() + file.virtual_signals.len() - 1;
+    if let Err(error) = load_signal_ids(file, &[id]) {
+        file.virtual_signals.pop();
+        return Err(error);
+    }
+    build_virtual_vars(file, None)
+        .into_iter()
+        .find(|var| var.ref_ == id)
+        .ok_or_else(|| "Signal not found".to_string())
+}
+
+#[wasm_bindgen]
+pub fn add_virtual_signal_wasm(filename: String, name: String, expression: String) -> Result<JsValue, String> {
+    let var = add_virtual_signal(filename, name, expression)?;
+    serde_wasm_bindgen::to_value(&var).map_err(|e| e.to_string())
+}
+
+/// Adds a virtual signal for each field of a bus, named after the field. Field ranges
+/// use the bus's declared indices. Either every field is added or none is.
+pub fn add_bus_fields(filename: String, signal_id: usize, fields: Vec<BusField>) -> Result<Vec<HierarchyVar>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    let path = signal_path(file, signal_id)?;
+    let count = file.virtual_signals.len();
+    let mut vars = Vec::new();
+    for field in fields {
+        let expression = if field.msb == field.lsb {
+            format!("{}[{}]", path, field.msb)
+        } else {
+            format!("{}[{}:{}]", path, field.msb, field.lsb)
+        };
+        match add_virtual(file, field.name, expression) {
+            Ok(var) => vars.push(var),
+            Err(error) => {
+                file.virtual_signals.truncate(count);
+                return Err(error);
+            }
+        }
+    }
+    Ok(vars)
+}
+
+#[wasm_bindgen]
+pub fn add_bus_fields_wasm(filename: String, signal_id: usize, fields: JsValue) -> Result<JsValue, String> {
+    let fields: Vec<BusField> = serde_wasm_bindgen::from_value(fields).map_err(|e| e.to_string())?;
+    let vars = add_bus_fields(filename, signal_id, fields)?;
+    serde_wasm_bindgen::to_value(&vars).map_err(|e| e.to_string())
+}
+
+/// Adds a virtual signal concatenating signals, most significant first, such as the
+/// single-bit vars of a bus.
+pub fn assemble_bus(filename: String, name: String, signal_ids: Vec<usize>) -> Result<HierarchyVar, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    if signal_ids.is_empty() {
+        return Err("A bus needs at least one signal".to_string());
+    }
+    let parts = signal_ids.iter().map(|id| signal_path(file, *id)).collect::<Result<Vec<_>, _>>()?;
+    add_virtual(file, name, bus::concatenation(&parts))
+}
+
+#[wasm_bindgen]
+pub fn assemble_bus_wasm(filename: String, name: String, signal_ids: Vec<usize>) -> Result<JsValue, String> {
+    let var = assemble_bus(filename, name, signal_ids)?;
+    serde_wasm_bindgen::to_value(&var).map_err(|e| e.to_string())
+}
+
+/// Removes a virtual signal. The ids of the other signals do not change.
+#[wasm_bindgen]
+pub fn remove_virtual_signal(filename: String, signal_id: usize) -> Result<(), String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    virtual_signal(file, signal_id)?;
+    let index = signal_id - file.wave.hierarchy().num_unique_signals();
+    file.virtual_signals[index] = None;
+    file.min_max_pyramids.remove(&signal_id);
+    file.signal_translations.remove(&signal_id);
+    file.signal_translators.remove(&signal_id);
+    file.interpolations.remove(&signal_id);
+    file.clocks.remove(&signal_id);
+    file.cycle_axes.retain(|(clock_id, _), _| *clock_id != signal_id);
+    file.activity_maps.remove(&ActivitySource::Signal { signal_id });
+    Ok(())
+}
+
+/// Adds a value-to-label table, replacing any table of the same name, and returns its
+/// number of entries. See the `translation` module for the file formats.
+pub fn add_translation_table(
+    filename: String,
+    name: String,
+    content: String,
+    format: TranslationFormat,
+) -> Result<usize, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    let table = translation::TranslationTable::parse(&content, format)?;
+    let len = table.len();
+    file.translation_tables.insert(name, table);
+    Ok(len)
+}
+
+#[wasm_bindgen]
+pub fn add_translation_table_wasm(filename: String, name: String, content: String, format: JsValue) -> Result<usize, String> {
+    let format: TranslationFormat = serde_wasm_bindgen::from_value(format).map_err(|e| e.to_string())?;
+    add_translation_table(filename, name, content, format)
+}
+
+/// Names of the file's translation tables, including those of its enum types.
+pub fn get_translation_tables(filename: String) -> Result<Vec<String>, String> {
+    let files = OPENED_FILES.lock().unwrap();
+    let file = find_file(&files, &filename)?;
+    Ok(file.translation_tables.keys().cloned().collect())
+}
+
+#[wasm_bindgen]
+pub fn get_translation_tables_wasm(filename: String) -> Result<Vec<String>, String> {
+    get_translation_tables(filename)
+}
+
+/// Attaches a translation table to a signal, so that its values come back as labels, or
+/// detaches it with `None`.
+pub fn set_signal_translation(filename: String, signal_id: usize, table: Option<String>) -> Result<(), String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    if to_signal_ref(&file.wave, signal_id).is_err() {
+        virtual_signal(file, signal_id)?;
+    }
+    match table {
+        Some(name) if !file.translation_tables.contains_key(&name) => Err(format!("Translation table not found: {}", name)),
+        Some(name) => {
+            file.signal_translations.insert(signal_id, name);
+            Ok(())
+        }
+        None => {
+            file.signal_translations.remove(&signal_id);
+            Ok(())
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_signal_translation_wasm(filename: String, signal_id: usize, table: Option<String>) -> Result<(), String> {
+    set_signal_translation(filename, signal_id, table)
+}
+
+/// Attaches a translator to a signal, replacing the one it had, or detaches it with `None`.
+/// Not exposed to JS: translators are Rust objects, like the native app's external processes.
+pub fn set_signal_translator(
+    filename: String,
+    signal_id: usize,
+    translator: Option<Box<dyn ValueTranslator>>,
+) -> Result<(), String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    if to_signal_ref(&file.wave, signal_id).is_err() {
+        virtual_signal(file, signal_id)?;
+    }
+    match translator {
+        Some(translator) => file.signal_translators.insert(signal_id, Arc::new(Mutex::new(translator))),
+        None => file.signal_translators.remove(&signal_id),
+    };
+    Ok(())
+}
+
+/// Finds where a condition over several signals holds, one page of at most
+/// `query.max_results` matches at a time. Pass `next_start` back as `query.start` to get
+/// the next page.
+pub fn search_temporal(filename: String, query: TemporalQuery) -> Result<TemporalSearchResults, String> {
+    // Registered first, so that a search waiting for another command can be cancelled too
+    let search = ActiveSearch::start(query.search_id);
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    let parsed = query
+        .condition
+        .expressions()
+        .into_iter()
+        .map(|text| parse_unclocked(file, text))
+        .collect::<Result<Vec<_>, _>>()?;
+    let signal_ids: Vec<usize> = parsed.iter().flat_map(|e| e.operands()).copied().collect();
+    load_signal_ids(file, &signal_ids)?;
+
+    let bound = parsed
+        .iter()
+        .map(|expression| {
+            let sources = expression
+                .operands()
+                .iter()
+                .map(|id| signal_data(file, *id).map(|(signal, _)| signal))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(temporal::BoundExpression { expression, sources })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    temporal::search(&query, &bound, file.wave.time_table(), &|| search.cancelled())
+}
+
+#[wasm_bindgen]
+pub fn search_temporal_wasm(filename: String, query: JsValue) -> Result<JsValue, String> {
+    let query: TemporalQuery = serde_wasm_bindgen::from_value(query).map_err(|e| e.to_string())?;
+    let results = search_temporal(filename, query)?;
+    serde_wasm_bindgen::to_value(&results).map_err(|e| e.to_string())
+}
+
+/// Makes the running search with this id return an error. Ids of searches that are not
+/// running are ignored. Native builds only: on the web, a search runs on the page's only
+/// thread, so no cancellation can arrive before it is over.
+#[wasm_bindgen]
+pub fn cancel_temporal_search(search_id: u32) {
+    for (active, cancelled) in ACTIVE_SEARCHES.lock().unwrap().iter_mut() {
+        if *active == search_id {
+            *cancelled = true;
+        }
+    }
+}
+
+/// Checks an assertion at every tick of its clock. See the `assertion` module for the
+/// property syntax. The counts cover every attempt; with `failures_only` the list of
+/// attempts holds only the failing ones.
+pub fn check_assertion(filename: String, property: String, failures_only: bool) -> Result<AssertionReport, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    let property = assertion::Property::parse(&property, |path, index| resolve_signal(file, path, index))?;
+    let signal_ids = property.signal_ids();
+    load_signal_ids(file, &signal_ids)?;
+    let sources = signal_ids
+        .iter()
+        .map(|id| signal_data(file, *id).map(|(signal, _)| (*id, signal)))
+        .collect::<Result<HashMap<_, _>, String>>()?;
+
+    Ok(assertion::check(&property, &sources, file.wave.time_table(), failures_only))
+}
+
+#[wasm_bindgen]
+pub fn check_assertion_wasm(filename: String, property: String, failures_only: bool) -> Result<JsValue, String> {
+    let report = check_assertion(filename, property, failures_only)?;
+    serde_wasm_bindgen::to_value(&report).map_err(|e| e.to_string())
+}
+
+/// Same window as `get_signal_changes`, encoded as a compact binary buffer.
+/// See the `packed` module for the layout.
+pub fn get_signal_changes_packed(filename: String, signal_id: usize, start: u64, end: u64) -> Result<Vec<u8>, String> {
+    let mut files = OPENED_FILES.lock().unwrap();
+    let file = find_file_mut(&mut files, &filename)?;
+
+    load_signal_ids(file, &[signal_id])?;
+
+    let (signal, encoding) = signal_data(file, signal_id)?;
+    let time_table = file.wave.time_table();
+    let range = changes::window_range(signal, time_table, start, end);
+
+    Ok(packed::encode(signal, time_table, range, encoding))
+}
+
+#[wasm_bindgen]
+pub fn get_signal_changes_packed_wasm(filename: String, signal_id: usize, start: u64, end: u64) -> Result<Vec<u8>, String> {
+    get_signal_changes_packed(filename, signal_id, start, end)
+}
+
+#[wasm_bindgen]
+pub fn get_signal_changes_batch_wasm(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: u64,
+    format: JsValue,
+) -> Result<JsValue, String> {
+    let changes = get_signal_changes_batch(filename, signal_ids, start, end, from_optional_js(format)?)?;
+    // Keyed results become a plain object rather than a JS Map
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    changes.serialize(&serializer).map_err(|e| e.to_string())
+}
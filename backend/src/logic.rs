@@ -0,0 +1,340 @@
+//! Four-state bit-vector operations with Verilog semantics. Vectors hold one state per bit,
+//! msb first, using the codes 0, 1, x and z. Operands of different widths are zero-extended
+//! to the wider one and all arithmetic is unsigned.
+
+use crate::value::{state_to_bit, STATE_0, STATE_1, STATE_X, STATE_Z};
+
+/// Reduces any state to 0, 1, x or z: weak levels become strong, the rest becomes x.
+pub(crate) fn to_four_state(state: u8) -> u8 {
+    match (state_to_bit(state), state) {
+        (Some(bit), _) => bit as u8,
+        (None, STATE_Z) => STATE_Z,
+        (None, _) => STATE_X,
+    }
+}
+
+fn is_known(state: u8) -> bool {
+    state == STATE_0 || state == STATE_1
+}
+
+pub(crate) fn unknown(width: usize) -> Vec<u8> {
+    vec![STATE_X; width]
+}
+
+pub(crate) fn from_bool(value: bool) -> Vec<u8> {
+    vec![value as u8]
+}
+
+/// Zero-extends or truncates to `width`, keeping the low bits.
+pub(crate) fn resize(value: &[u8], width: usize) -> Vec<u8> {
+    if value.len() >= width {
+        value[value.len() - width..].to_vec()
+    } else {
+        let mut out = vec![STATE_0; width - value.len()];
+        out.extend_from_slice(value);
+        out
+    }
+}
+
+fn extend_pair(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let width = a.len().max(b.len());
+    (resize(a, width), resize(b, width))
+}
+
+/// Bits of a fully known vector, lsb first. `None` if any bit is x or z.
+fn to_bits_lsb(value: &[u8]) -> Option<Vec<bool>> {
+    value.iter().rev().map(|s| if is_known(*s) { Some(*s == STATE_1) } else { None }).collect()
+}
+
+fn from_bits_lsb(bits: &[bool]) -> Vec<u8> {
+    bits.iter().rev().map(|b| *b as u8).collect()
+}
+
+/// Value of a known vector as an integer, saturating at `usize::MAX`.
+pub(crate) fn to_usize(value: &[u8]) -> Option<usize> {
+    let bits = to_bits_lsb(value)?;
+    let mut out = 0usize;
+    for (i, bit) in bits.iter().enumerate() {
+        if *bit {
+            if i >= usize::BITS as usize {
+                return Some(usize::MAX);
+            }
+            out |= 1 << i;
+        }
+    }
+    Some(out)
+}
+
+pub(crate) fn not(value: &[u8]) -> Vec<u8> {
+    value
+        .iter()
+        .map(|s| match *s {
+            STATE_0 => STATE_1,
+            STATE_1 => STATE_0,
+            _ => STATE_X,
+        })
+        .collect()
+}
+
+fn and_bit(a: u8, b: u8) -> u8 {
+    match (a, b) {
+        (STATE_0, _) | (_, STATE_0) => STATE_0,
+        (STATE_1, STATE_1) => STATE_1,
+        _ => STATE_X,
+    }
+}
+
+fn or_bit(a: u8, b: u8) -> u8 {
+    match (a, b) {
+        (STATE_1, _) | (_, STATE_1) => STATE_1,
+        (STATE_0, STATE_0) => STATE_0,
+        _ => STATE_X,
+    }
+}
+
+fn xor_bit(a: u8, b: u8) -> u8 {
+    if is_known(a) && is_known(b) { a ^ b } else { STATE_X }
+}
+
+fn bitwise(a: &[u8], b: &[u8], op: fn(u8, u8) -> u8) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    a.iter().zip(&b).map(|(a, b)| op(*a, *b)).collect()
+}
+
+pub(crate) fn and(a: &[u8], b: &[u8]) -> Vec<u8> {
+    bitwise(a, b, and_bit)
+}
+
+pub(crate) fn or(a: &[u8], b: &[u8]) -> Vec<u8> {
+    bitwise(a, b, or_bit)
+}
+
+pub(crate) fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    bitwise(a, b, xor_bit)
+}
+
+pub(crate) fn reduce_and(value: &[u8]) -> Vec<u8> {
+    vec![value.iter().fold(STATE_1, |acc, s| and_bit(acc, *s))]
+}
+
+pub(crate) fn reduce_or(value: &[u8]) -> Vec<u8> {
+    vec![value.iter().fold(STATE_0, |acc, s| or_bit(acc, *s))]
+}
+
+pub(crate) fn reduce_xor(value: &[u8]) -> Vec<u8> {
+    vec![value.iter().fold(STATE_0, |acc, s| xor_bit(acc, *s))]
+}
+
+/// 1 if any bit is 1, 0 if all bits are 0, x otherwise.
+pub(crate) fn truth(value: &[u8]) -> u8 {
+    reduce_or(value)[0]
+}
+
+pub(crate) fn logical_not(value: &[u8]) -> Vec<u8> {
+    not(&[truth(value)])
+}
+
+pub(crate) fn logical_and(a: &[u8], b: &[u8]) -> Vec<u8> {
+    vec![and_bit(truth(a), truth(b))]
+}
+
+pub(crate) fn logical_or(a: &[u8], b: &[u8]) -> Vec<u8> {
+    vec![or_bit(truth(a), truth(b))]
+}
+
+/// Applies an operation on known bits (lsb first); any unknown operand bit makes the whole result x.
+fn arithmetic(a: &[u8], b: &[u8], op: impl FnOnce(&[bool], &[bool]) -> Option<Vec<bool>>) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    match (to_bits_lsb(&a), to_bits_lsb(&b)) {
+        (Some(a_bits), Some(b_bits)) => match op(&a_bits, &b_bits) {
+            Some(bits) => from_bits_lsb(&bits),
+            None => unknown(a.len()),
+        },
+        _ => unknown(a.len()),
+    }
+}
+
+fn add_bits(a: &[bool], b: &[bool], carry_in: bool) -> Vec<bool> {
+    let mut carry = carry_in;
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| {
+            let sum = a ^ b ^ carry;
+            carry = (a & b) | (carry & (a ^ b));
+            sum
+        })
+        .collect()
+}
+
+fn sub_bits(a: &[bool], b: &[bool]) -> Vec<bool> {
+    let inverted: Vec<bool> = b.iter().map(|b| !b).collect();
+    add_bits(a, &inverted, true)
+}
+
+/// `a >= b` for equal-width lsb-first vectors.
+fn ge_bits(a: &[bool], b: &[bool]) -> bool {
+    for (a, b) in a.iter().rev().zip(b.iter().rev()) {
+        if a != b {
+            return *a;
+        }
+    }
+    true
+}
+
+/// Long division, `None` when dividing by zero.
+fn div_rem_bits(a: &[bool], b: &[bool]) -> Option<(Vec<bool>, Vec<bool>)> {
+    if !b.iter().any(|b| *b) {
+        return None;
+    }
+    let mut quotient = vec![false; a.len()];
+    let mut remainder = vec![false; a.len()];
+    for i in (0..a.len()).rev() {
+        remainder.rotate_right(1);
+        remainder[0] = a[i];
+        if ge_bits(&remainder, b) {
+            remainder = sub_bits(&remainder, b);
+            quotient[i] = true;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+pub(crate) fn add(a: &[u8], b: &[u8]) -> Vec<u8> {
+    arithmetic(a, b, |a, b| Some(add_bits(a, b, false)))
+}
+
+pub(crate) fn sub(a: &[u8], b: &[u8]) -> Vec<u8> {
+    arithmetic(a, b, |a, b| Some(sub_bits(a, b)))
+}
+
+pub(crate) fn mul(a: &[u8], b: &[u8]) -> Vec<u8> {
+    arithmetic(a, b, |a, b| {
+        let mut product = vec![false; a.len()];
+        for (shift, bit) in b.iter().enumerate() {
+            if *bit {
+                let mut shifted = vec![false; shift];
+                shifted.extend_from_slice(&a[..a.len() - shift]);
+                product = add_bits(&product, &shifted, false);
+            }
+        }
+        Some(product)
+    })
+}
+
+pub(crate) fn div(a: &[u8], b: &[u8]) -> Vec<u8> {
+    arithmetic(a, b, |a, b| div_rem_bits(a, b).map(|(quotient, _)| quotient))
+}
+
+pub(crate) fn rem(a: &[u8], b: &[u8]) -> Vec<u8> {
+    arithmetic(a, b, |a, b| div_rem_bits(a, b).map(|(_, remainder)| remainder))
+}
+
+pub(crate) fn negate(value: &[u8]) -> Vec<u8> {
+    sub(&vec![STATE_0; value.len()], value)
+}
+
+/// Shifts in zeros; the shifted value keeps its unknown bits, an unknown amount gives all x.
+pub(crate) fn shift(value: &[u8], amount: &[u8], left: bool) -> Vec<u8> {
+    let Some(amount) = to_usize(amount) else {
+        return unknown(value.len());
+    };
+    let width = value.len();
+    let amount = amount.min(width);
+    let mut out = vec![STATE_0; width];
+    if left {
+        out[..width - amount].copy_from_slice(&value[amount..]);
+    } else {
+        out[amount..].copy_from_slice(&value[..width - amount]);
+    }
+    out
+}
+
+/// Logical equality: 0 if any pair of known bits differs, x if the rest is undecided.
+pub(crate) fn equal(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    let mut result = STATE_1;
+    for (a, b) in a.iter().zip(&b) {
+        if is_known(*a) && is_known(*b) {
+            if a != b {
+                return vec![STATE_0];
+            }
+        } else {
+            result = STATE_X;
+        }
+    }
+    vec![result]
+}
+
+/// Case equality: x and z bits must match exactly.
+pub(crate) fn case_equal(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    from_bool(a == b)
+}
+
+/// Unsigned `a < b`, x when either side has unknown bits.
+pub(crate) fn less_than(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    match (to_bits_lsb(&a), to_bits_lsb(&b)) {
+        (Some(a), Some(b)) => from_bool(!ge_bits(&a, &b)),
+        _ => vec![STATE_X],
+    }
+}
+
+/// `cond ? a : b`. An unknown condition keeps the bits on which both sides agree.
+pub(crate) fn select(cond: &[u8], a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (a, b) = extend_pair(a, b);
+    match truth(cond) {
+        STATE_1 => a,
+        STATE_0 => b,
+        _ => a.iter().zip(&b).map(|(a, b)| if a == b && is_known(*a) { *a } else { STATE_X }).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(text: &str) -> Vec<u8> {
+        text.chars().map(|c| "01xz".find(c).unwrap() as u8).collect()
+    }
+
+    #[test]
+    fn test_bitwise_with_unknown_bits() {
+        assert_eq!(and(&bits("01xz"), &bits("0000")), bits("0000"));
+        assert_eq!(and(&bits("01xz"), &bits("1111")), bits("01xx"));
+        assert_eq!(or(&bits("01xz"), &bits("1111")), bits("1111"));
+        assert_eq!(xor(&bits("0101"), &bits("0x11")), bits("0x10"));
+        assert_eq!(not(&bits("01z")), bits("10x"));
+        assert_eq!(reduce_and(&bits("1x0")), bits("0"));
+        assert_eq!(reduce_or(&bits("0x0")), bits("x"));
+        assert_eq!(logical_and(&bits("010"), &bits("x")), bits("x"));
+        assert_eq!(logical_or(&bits("010"), &bits("x")), bits("1"));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(add(&bits("0111"), &bits("0001")), bits("1000"));
+        assert_eq!(add(&bits("1111"), &bits("1")), bits("0000"));
+        assert_eq!(sub(&bits("0010"), &bits("0011")), bits("1111"));
+        assert_eq!(mul(&bits("0011"), &bits("0101")), bits("1111"));
+        assert_eq!(div(&bits("1110"), &bits("0011")), bits("0100"));
+        assert_eq!(rem(&bits("1110"), &bits("0011")), bits("0010"));
+        assert_eq!(div(&bits("1110"), &bits("0000")), bits("xxxx"));
+        assert_eq!(add(&bits("01x1"), &bits("0001")), bits("xxxx"));
+        assert_eq!(negate(&bits("0001")), bits("1111"));
+        assert_eq!(shift(&bits("0x11"), &bits("01"), true), bits("x110"));
+        assert_eq!(shift(&bits("1011"), &bits("10"), false), bits("0010"));
+    }
+
+    #[test]
+    fn test_comparisons_and_select() {
+        assert_eq!(equal(&bits("1x00"), &bits("0x00")), bits("0"));
+        assert_eq!(equal(&bits("1x00"), &bits("1000")), bits("x"));
+        assert_eq!(equal(&bits("11"), &bits("0011")), bits("1"));
+        assert_eq!(case_equal(&bits("1x"), &bits("1x")), bits("1"));
+        assert_eq!(less_than(&bits("0011"), &bits("0100")), bits("1"));
+        assert_eq!(less_than(&bits("0x11"), &bits("0100")), bits("x"));
+        assert_eq!(select(&bits("x"), &bits("1100"), &bits("1010")), bits("1xx0"));
+        assert_eq!(select(&bits("1"), &bits("1100"), &bits("1010")), bits("1100"));
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use wellen::{SignalEncoding, SignalValue, Time, TimeTableIdx};
+
+use crate::changes::{first_change_after, first_change_at_or_after, iter_range, value_at, SignalData};
+use crate::value::{bit_states, parse_literal, same_value, state_to_bit, STATE_H, STATE_L};
+
+/// Which change to look for. Changes that repeat the previous value never match.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NavigationTarget {
+    /// Any change of value.
+    Change,
+    /// A 1-bit signal going to 1 from 0, x or z.
+    RisingEdge,
+    /// A 1-bit signal going to 0 from 1, x or z.
+    FallingEdge,
+    /// The value becoming equal to `value`. Bit vectors take a literal such as `8'hff`,
+    /// `0x1f` or `12`; reals a number and strings the exact text.
+    Equals { value: String },
+    /// Any change to a value other than `value`.
+    NotEquals { value: String },
+    /// A bit vector going from fully known to having x, z or other unknown bits.
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationDirection {
+    #[default]
+    Forward,
+    Backward,
+}
+
+/// Value given to `Equals` and `NotEquals`, parsed for the signal's encoding.
+enum Expected {
+    Bits(Vec<u8>),
+    Real(f64),
+    Text(String),
+}
+
+impl Expected {
+    fn parse(value: &str, encoding: SignalEncoding) -> Result<Self, String> {
+        match encoding {
+            SignalEncoding::BitVector(width) => Ok(Expected::Bits(parse_literal(value, width.get())?)),
+            SignalEncoding::Real => value
+                .trim()
+                .parse()
+                .map(Expected::Real)
+                .map_err(|_| format!("Invalid real value: {}", value)),
+            SignalEncoding::String => Ok(Expected::Text(value.to_string())),
+        }
+    }
+
+    fn matches(&self, value: &SignalValue) -> bool {
+        match (self, value) {
+            (Expected::Real(expected), SignalValue::Real(actual)) => expected == actual,
+            (Expected::Text(expected), SignalValue::String(actual)) => expected == actual,
+            (Expected::Bits(expected), _) => bit_states(value).is_some_and(|states| {
+                // Weak levels compare equal to the strong ones
+                let strong = |state: u8| match state {
+                    STATE_H => 1,
+                    STATE_L => 0,
+                    state => state,
+                };
+                states.len() == expected.len() && states.iter().zip(expected).all(|(a, b)| strong(*a) == *b)
+            }),
+            _ => false,
+        }
+    }
+}
+
+fn single_bit(value: &SignalValue) -> Option<bool> {
+    bit_states(value).and_then(|states| state_to_bit(*states.first()?))
+}
+
+fn has_unknown(value: &SignalValue) -> bool {
+    bit_states(value).is_some_and(|states| states.iter().any(|s| state_to_bit(*s).is_none()))
+}
+
+/// Time of the first change strictly after `from` (forward) or the last change strictly
+/// before it (backward) that matches `target`, or `None` if there is no such change.
+pub(crate) fn find(
+    signal: SignalData,
+    time_table: &[Time],
+    encoding: SignalEncoding,
+    from: Time,
+    direction: NavigationDirection,
+    target: &NavigationTarget,
+) -> Result<Option<Time>, String> {
+    let expected = match target {
+        NavigationTarget::RisingEdge | NavigationTarget::FallingEdge
+            if !matches!(encoding, SignalEncoding::BitVector(width) if width.get() == 1) =>
+        {
+            return Err("Edge navigation needs a 1-bit signal".to_string());
+        }
+        NavigationTarget::Unknown if !matches!(encoding, SignalEncoding::BitVector(_)) => {
+            return Err("Only bit vectors can be unknown".to_string());
+        }
+        NavigationTarget::Equals { value } | NavigationTarget::NotEquals { value } => {
+            Some(Expected::parse(value, encoding)?)
+        }
+        _ => None,
+    };
+    let is_match = |previous: Option<&SignalValue>, value: &SignalValue| {
+        if previous.is_some_and(|previous| same_value(previous, value)) {
+            return false;
+        }
+        match (target, &expected) {
+            (NavigationTarget::Change, _) => true,
+            (NavigationTarget::RisingEdge, _) => previous.is_some() && single_bit(value) == Some(true),
+            (NavigationTarget::FallingEdge, _) => previous.is_some() && single_bit(value) == Some(false),
+            (NavigationTarget::Equals { .. }, Some(expected)) => expected.matches(value),
+            (NavigationTarget::NotEquals { .. }, Some(expected)) => !expected.matches(value),
+            (NavigationTarget::Unknown, _) => has_unknown(value) && !previous.is_some_and(has_unknown),
+            _ => false,
+        }
+    };
+
+    // Each candidate change paired with the one before it
+    let time_indices = signal.time_indices();
+    let candidates: Box<dyn Iterator<Item = ((TimeTableIdx, SignalValue), Option<SignalValue>)>> = match direction {
+        NavigationDirection::Forward => {
+            let first = first_change_after(time_indices, time_table, from);
+            let previous = first.checked_sub(1).and_then(|index| value_at(signal, index));
+            let previous_values = std::iter::once(previous).chain(iter_range(signal, first..time_indices.len()).map(|(_, v)| Some(v)));
+            Box::new(iter_range(signal, first..time_indices.len()).zip(previous_values))
+        }
+        NavigationDirection::Backward => {
+            let end = first_change_at_or_after(time_indices, time_table, from);
+            let previous_values = iter_range(signal, 0..end.saturating_sub(1)).rev().map(|(_, v)| Some(v)).chain(std::iter::once(None));
+            Box::new(iter_range(signal, 0..end).rev().zip(previous_values))
+        }
+    };
+
+    for ((time_idx, value), previous) in candidates {
+        if is_match(previous.as_ref(), &value) {
+            return Ok(Some(time_table[time_idx as usize]));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,58 @@
+//! Which signals changed around a time, for asking what caused an event.
+
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{first_change_after, first_change_at_or_after, iter_range, SignalData};
+use crate::value::same_value;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NearbyChange {
+    pub(crate) signal_id: usize,
+    pub(crate) path: String,
+    /// Time of the signal's transition closest to the queried time, the earlier on ties.
+    pub(crate) time: u64,
+    /// How far that transition is from the queried time.
+    pub(crate) distance: u64,
+    /// `None` for the signal's first value.
+    pub(crate) old_value: Option<String>,
+    pub(crate) new_value: String,
+    /// Number of transitions of the signal in the window.
+    pub(crate) transitions: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NearbyChanges {
+    /// Closest first, at most the requested number.
+    pub(crate) changes: Vec<NearbyChange>,
+    /// Number of vars with a transition in the window.
+    pub(crate) total: usize,
+}
+
+/// Finds the transitions of a signal in `[time - radius, time + radius]`, skipping
+/// changes that repeat the previous value. Returns the position of the closest one in
+/// the signal's change list, its time and the number of transitions.
+pub(crate) fn closest_transition(signal: SignalData, time_table: &[Time], time: u64, radius: u64) -> Option<(usize, u64, usize)> {
+    let time_indices = signal.time_indices();
+    let first = first_change_at_or_after(time_indices, time_table, time.saturating_sub(radius));
+    let last = first_change_after(time_indices, time_table, time.saturating_add(radius));
+    if first >= last {
+        return None;
+    }
+
+    let mut closest: Option<(usize, u64)> = None;
+    let mut transitions = 0;
+    let mut changes = iter_range(signal, first.saturating_sub(1)..last);
+    let mut previous = if first > 0 { changes.next().map(|(_, value)| value) } else { None };
+    for (index, (time_idx, value)) in (first..last).zip(changes) {
+        if previous.as_ref().is_none_or(|previous| !same_value(previous, &value)) {
+            let change_time = time_table[time_idx as usize];
+            transitions += 1;
+            if closest.is_none_or(|(_, closest_time)| change_time.abs_diff(time) < closest_time.abs_diff(time)) {
+                closest = Some((index, change_time));
+            }
+        }
+        previous = Some(value);
+    }
+    closest.map(|(index, change_time)| (index, change_time, transitions))
+}
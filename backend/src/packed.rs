@@ -0,0 +1,120 @@
+//! Compact binary encoding of a window of signal changes.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | offset | size        | content                                                  |
+//! |--------|-------------|----------------------------------------------------------|
+//! | 0      | 1           | kind: 0 = bit vector, 1 = real, 2 = string               |
+//! | 1      | 1           | bits per state for bit vectors (1, 2 or 4), else 0       |
+//! | 2      | 2           | reserved                                                 |
+//! | 4      | 4           | width in bits for bit vectors, else 0                    |
+//! | 8      | 4           | number of changes `n`                                    |
+//! | 12     | 4           | reserved, keeps the times 8-byte aligned                 |
+//! | 16     | 8 * n       | times as u64                                             |
+//! | ...    | payload     | bit vectors: `n` rows of `ceil(width * bits / 8)` bytes; |
+//! |        |             | reals: `n` f64; strings: `n` times (u32 length, UTF-8)   |
+//!
+//! Bit vector rows store the states msb first, packed from the high bits of each byte,
+//! using wellen's state codes (0, 1, x, z, h, u, w, l, -). Two-state windows use one bit
+//! per state, four-state windows two and nine-state windows four.
+
+use std::ops::Range;
+
+use wellen::{SignalValue, Time};
+
+use crate::changes::{iter_range, SignalData};
+use crate::value::bit_states;
+
+pub const KIND_BITS: u8 = 0;
+pub const KIND_REAL: u8 = 1;
+pub const KIND_STRING: u8 = 2;
+
+const HEADER_LEN: usize = 16;
+
+pub(crate) fn encode(signal: SignalData, time_table: &[Time], range: Range<usize>, encoding: wellen::SignalEncoding) -> Vec<u8> {
+    let count = range.len();
+    let (kind, width) = match encoding {
+        wellen::SignalEncoding::BitVector(width) => (KIND_BITS, width.get()),
+        wellen::SignalEncoding::Real => (KIND_REAL, 0),
+        wellen::SignalEncoding::String => (KIND_STRING, 0),
+    };
+    let bits_per_state = if kind == KIND_BITS {
+        iter_range(signal, range.clone())
+            .map(|(_, value)| match value {
+                SignalValue::Binary(..) => 1,
+                SignalValue::FourValue(..) => 2,
+                _ => 4,
+            })
+            .max()
+            .unwrap_or(1)
+    } else {
+        0
+    };
+
+    let mut out = Vec::with_capacity(HEADER_LEN + count * 8);
+    out.push(kind);
+    out.push(bits_per_state);
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&(count as u32).to_le_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0]);
+
+    for (time_idx, _) in iter_range(signal, range.clone()) {
+        out.extend_from_slice(&time_table[time_idx as usize].to_le_bytes());
+    }
+
+    let row_len = (width as usize * bits_per_state as usize).div_ceil(8);
+    for (_, value) in iter_range(signal, range) {
+        match value {
+            SignalValue::Real(value) => out.extend_from_slice(&value.to_le_bytes()),
+            SignalValue::String(value) => {
+                out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                out.extend_from_slice(value.as_bytes());
+            }
+            _ => {
+                let states = bit_states(&value).unwrap_or_default();
+                let row_start = out.len();
+                out.resize(row_start + row_len, 0);
+                for (i, state) in states.iter().enumerate() {
+                    let bit = i * bits_per_state as usize;
+                    let shift = 8 - bits_per_state as usize - bit % 8;
+                    out[row_start + bit / 8] |= state << shift;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_encode_four_state_bus() {
+        let mut waveform = wellen::simple::read("../examples/simple.vcd").unwrap();
+        let hierarchy = waveform.hierarchy();
+        let r_reg = hierarchy
+            .iter_vars()
+            .find(|v| v.name(hierarchy) == "r_reg")
+            .unwrap();
+        let (signal_ref, encoding) = (r_reg.signal_ref(), r_reg.signal_encoding());
+        waveform.load_signals(&[signal_ref]);
+        let signal = waveform.get_signal(signal_ref).unwrap();
+
+        // The first two changes: bx at t=0 and b0 at t=5
+        let bytes = encode(SignalData::Loaded(signal), waveform.time_table(), 0..2, encoding);
+        assert_eq!(bytes[0], KIND_BITS);
+        assert_eq!(bytes[1], 2);
+        assert_eq!(read_u32(&bytes, 4), 3);
+        assert_eq!(read_u32(&bytes, 8), 2);
+        assert_eq!(u64::from_le_bytes(bytes[16..24].try_into().unwrap()), 0);
+        assert_eq!(u64::from_le_bytes(bytes[24..32].try_into().unwrap()), 5);
+        // Three x states (code 2) packed from the high bits, then three zeros
+        assert_eq!(&bytes[32..], &[0b10_10_10_00, 0]);
+    }
+}
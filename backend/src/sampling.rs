@@ -0,0 +1,68 @@
+//! Tables of signal values sampled at every edge of a clock, as a cycle-accurate model
+//! sees them.
+
+use serde::{Deserialize, Serialize};
+use wellen::{SignalValue, Time};
+
+use crate::changes::{first_change_at_or_after, value_at, SignalData};
+use crate::cycles::ClockEdge;
+use crate::format::ValueFormat;
+
+#[derive(Serialize, Deserialize)]
+pub struct SampleQuery {
+    pub clock_id: usize,
+    #[serde(default)]
+    pub edge: ClockEdge,
+    /// The columns of the table.
+    pub signal_ids: Vec<usize>,
+    /// Edges in `[start, end]` give the rows; the whole trace by default.
+    #[serde(default)]
+    pub start: u64,
+    #[serde(default)]
+    pub end: Option<u64>,
+    /// The page skips `offset` rows and holds at most `limit`.
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: usize,
+    #[serde(default)]
+    pub format: Option<ValueFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SampledRow {
+    /// Time of the clock edge.
+    pub(crate) time: u64,
+    /// Number of the edge from the start of the trace, as on the cycle axis.
+    pub(crate) cycle: usize,
+    /// One value per column, `None` before a signal's first change.
+    pub(crate) values: Vec<Option<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SampledTable {
+    pub(crate) rows: Vec<SampledRow>,
+    /// Number of rows in the whole window, over all pages.
+    pub(crate) total_rows: usize,
+}
+
+/// Samples each column at the given edges, numbered as on the cycle axis. Like a flip-flop,
+/// an edge sees the value held just before it, not a change at the same time.
+pub(crate) fn sample_rows<R: Fn(&SignalValue) -> Result<String, String>>(
+    edges: &[(usize, u64)],
+    columns: &[(SignalData, R)],
+    time_table: &[Time],
+) -> Result<Vec<SampledRow>, String> {
+    edges
+        .iter()
+        .map(|(cycle, time)| {
+            let values = columns
+                .iter()
+                .map(|(signal, render)| {
+                    let held = first_change_at_or_after(signal.time_indices(), time_table, *time).checked_sub(1);
+                    held.and_then(|index| value_at(*signal, index)).map(|value| render(&value)).transpose()
+                })
+                .collect::<Result<_, String>>()?;
+            Ok(SampledRow { time: *time, cycle: *cycle, values })
+        })
+        .collect()
+}
@@ -0,0 +1,306 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{direction_name, var_type_name};
+
+/// Flat list of every var in a file with its full hierarchical path, built once when
+/// the file is added so searches never have to walk the hierarchy.
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+}
+
+struct IndexEntry {
+    path: String,
+    /// Byte offset of the local var name inside `path`.
+    name_start: usize,
+    var_ref: usize,
+    signal_ref: usize,
+    width: Option<u32>,
+    var_type: &'static str,
+    direction: &'static str,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Glob,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SearchQuery {
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only keep vars whose type is in this list (e.g. "wire", "reg"). Empty keeps all.
+    #[serde(default)]
+    pub var_types: Vec<String>,
+    /// Only keep vars whose direction is in this list (e.g. "input"). Empty keeps all.
+    #[serde(default)]
+    pub directions: Vec<String>,
+    #[serde(default)]
+    pub min_width: Option<u32>,
+    #[serde(default)]
+    pub max_width: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub(crate) path: String,
+    pub(crate) name: String,
+    pub(crate) var_ref: usize,
+    #[serde(rename = "ref")]
+    pub(crate) ref_: usize,
+    pub(crate) width: Option<u32>,
+    pub(crate) var_type: String,
+    pub(crate) direction: String,
+    /// Fuzzy match score, higher is better. `None` for glob and regex queries.
+    pub(crate) score: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchResults {
+    pub(crate) matches: Vec<SearchMatch>,
+    /// Number of matches before pagination.
+    pub(crate) total: usize,
+}
+
+impl SearchIndex {
+    pub fn build(hierarchy: &wellen::Hierarchy) -> Self {
+        let entries = hierarchy
+            .iter_vars()
+            .enumerate()
+            .map(|(var_ref, var)| {
+                let path = var.full_name(hierarchy);
+                let name_start = path.len() - var.name(hierarchy).len();
+                IndexEntry {
+                    path,
+                    name_start,
+                    var_ref,
+                    signal_ref: var.signal_ref().index(),
+                    width: var.length(),
+                    var_type: var_type_name(var.var_type()),
+                    direction: direction_name(var.direction()),
+                }
+            })
+            .collect();
+        SearchIndex { entries }
+    }
+
+    pub fn search(&self, query: &SearchQuery, offset: usize, limit: usize) -> Result<SearchResults, String> {
+        let candidates = self.entries.iter().filter(|entry| query.accepts(entry));
+
+        let mut hits: Vec<(&IndexEntry, Option<i64>)> = match query.mode {
+            SearchMode::Glob | SearchMode::Regex => {
+                let matcher = PathMatcher::new(query)?;
+                candidates
+                    .filter(|entry| matcher.is_match(entry))
+                    .map(|entry| (entry, None))
+                    .collect()
+            }
+            SearchMode::Fuzzy => {
+                let mut scored: Vec<_> = candidates
+                    .filter_map(|entry| {
+                        fuzzy_score(&query.pattern, &entry.path, entry.name_start, query.case_sensitive)
+                            .map(|score| (entry, Some(score)))
+                    })
+                    .collect();
+                scored.sort_by(|(a, a_score), (b, b_score)| {
+                    b_score
+                        .cmp(a_score)
+                        .then(a.path.len().cmp(&b.path.len()))
+                        .then(a.path.cmp(&b.path))
+                });
+                scored
+            }
+        };
+
+        let total = hits.len();
+        let matches = hits
+            .drain(..)
+            .skip(offset)
+            .take(limit)
+            .map(|(entry, score)| SearchMatch {
+                path: entry.path.clone(),
+                name: entry.path[entry.name_start..].to_string(),
+                var_ref: entry.var_ref,
+                ref_: entry.signal_ref,
+                width: entry.width,
+                var_type: entry.var_type.to_string(),
+                direction: entry.direction.to_string(),
+                score,
+            })
+            .collect();
+
+        Ok(SearchResults { matches, total })
+    }
+}
+
+impl SearchQuery {
+    fn accepts(&self, entry: &IndexEntry) -> bool {
+        if !self.var_types.is_empty() && !self.var_types.iter().any(|t| t == entry.var_type) {
+            return false;
+        }
+        if !self.directions.is_empty() && !self.directions.iter().any(|d| d == entry.direction) {
+            return false;
+        }
+        if self.min_width.is_some() || self.max_width.is_some() {
+            let Some(width) = entry.width else {
+                return false;
+            };
+            if self.min_width.is_some_and(|min| width < min) || self.max_width.is_some_and(|max| width > max) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Glob and regex queries both compile to a regex. A pattern without any `.` is
+/// matched against the local var name only, otherwise against the full path.
+struct PathMatcher {
+    regex: Regex,
+    name_only: bool,
+}
+
+impl PathMatcher {
+    fn new(query: &SearchQuery) -> Result<Self, String> {
+        let source = match query.mode {
+            SearchMode::Glob => glob_to_regex(&query.pattern),
+            _ => query.pattern.clone(),
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid search pattern: {}", e))?;
+        Ok(PathMatcher {
+            regex,
+            name_only: query.mode == SearchMode::Glob && !query.pattern.contains('.'),
+        })
+    }
+
+    fn is_match(&self, entry: &IndexEntry) -> bool {
+        if self.name_only {
+            self.regex.is_match(&entry.path[entry.name_start..])
+        } else {
+            self.regex.is_match(&entry.path)
+        }
+    }
+}
+
+/// Translates a glob into an anchored regex: `*` matches within one hierarchy level,
+/// `**` matches across levels and `?` matches a single character.
+fn glob_to_regex(pattern: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push_str(".*");
+            }
+            '*' => out.push_str("[^.]*"),
+            '?' => out.push_str("[^.]"),
+            _ => out.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    out.push('$');
+    out
+}
+
+/// Scores `pattern` as an ordered subsequence of `path`. Consecutive runs, matches at
+/// the start of a path segment or word, and matches inside the var name score higher;
+/// skipped characters cost a little. Returns `None` when not every character matches.
+fn fuzzy_score(pattern: &str, path: &str, name_start: usize, case_sensitive: bool) -> Option<i64> {
+    let normalize = |c: char| if case_sensitive { c } else { c.to_ascii_lowercase() };
+    let needle: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).map(normalize).collect();
+    if needle.is_empty() {
+        return Some(0);
+    }
+    let haystack: Vec<(usize, char)> = path.char_indices().map(|(i, c)| (i, normalize(c))).collect();
+
+    // Greedy forward pass to find where the match ends, then walk backwards from there
+    // so the chosen characters are as tightly packed as possible.
+    let mut end = None;
+    let mut next = 0;
+    for (pos, (_, c)) in haystack.iter().enumerate() {
+        if *c == needle[next] {
+            next += 1;
+            if next == needle.len() {
+                end = Some(pos);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut positions = vec![0; needle.len()];
+    let mut remaining = needle.len();
+    for pos in (0..=end).rev() {
+        if haystack[pos].1 == needle[remaining - 1] {
+            remaining -= 1;
+            positions[remaining] = pos;
+            if remaining == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut score = 0i64;
+    let mut previous: Option<usize> = None;
+    for &pos in &positions {
+        score += 16;
+        let at_boundary = pos == 0 || matches!(haystack[pos - 1].1, '.' | '_' | '[' | '/');
+        if at_boundary {
+            score += 10;
+        }
+        if haystack[pos].0 >= name_start {
+            score += 4;
+        }
+        match previous {
+            Some(prev) if pos == prev + 1 => score += 12,
+            Some(prev) => score -= (pos - prev - 1).min(8) as i64,
+            None => score -= pos.min(16) as i64 / 4,
+        }
+        previous = Some(pos);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_to_regex() {
+        let regex = Regex::new(&glob_to_regex("top.*.fifo*.wr_en")).unwrap();
+        assert!(regex.is_match("top.u0.fifo_in.wr_en"));
+        assert!(!regex.is_match("top.u0.sub.fifo_in.wr_en"));
+        assert!(!regex.is_match("top.u0.fifo_in.wr_en2"));
+
+        let regex = Regex::new(&glob_to_regex("top.**.wr_en")).unwrap();
+        assert!(regex.is_match("top.u0.sub.fifo_in.wr_en"));
+
+        let regex = Regex::new(&glob_to_regex("d?ta")).unwrap();
+        assert!(regex.is_match("data"));
+        assert!(!regex.is_match("d.ta"));
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_tight_and_boundary_matches() {
+        let tight = fuzzy_score("wren", "top.fifo.wren", 9, false).unwrap();
+        let spread = fuzzy_score("wren", "top.w_r_e_n", 4, false).unwrap();
+        assert!(tight > spread);
+
+        let name = fuzzy_score("clk", "top.clk", 4, false).unwrap();
+        let scope = fuzzy_score("clk", "clk_gen.enable", 8, false).unwrap();
+        assert!(name > scope);
+
+        assert!(fuzzy_score("xyz", "top.clk", 4, false).is_none());
+        assert!(fuzzy_score("CLK", "top.clk", 4, true).is_none());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use wellen::{SignalEncoding, SignalValue, Time};
+
+use crate::changes::{iter_range, window_range, SignalData};
+use crate::value;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct StableInterval {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    pub(crate) value: String,
+}
+
+/// Figures of a signal over a window `[start, end)`. Times are in file units; the span
+/// before a signal's first change has no value and counts towards none of them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SignalStatistics {
+    /// Value changes strictly inside the window.
+    pub(crate) transitions: usize,
+    /// Time spent at 1 and at 0, for 1-bit signals.
+    pub(crate) high_time: Option<u64>,
+    pub(crate) low_time: Option<u64>,
+    /// Share of the known time spent at 1, for 1-bit signals.
+    pub(crate) duty_cycle: Option<f64>,
+    /// Time spent with any bit X, Z or another non-logic state.
+    pub(crate) unknown_time: u64,
+    pub(crate) distinct_values: usize,
+    /// Numeric range of bit vectors without unknown bits and reals. The mean is weighted
+    /// by the time each value is held.
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    pub(crate) mean: Option<f64>,
+    /// Longest span without a value change, clipped to the window. The first one wins ties.
+    pub(crate) longest_stable: Option<StableInterval>,
+}
+
+/// Walks the changes of the window as spans of constant value.
+pub(crate) fn statistics(
+    signal: SignalData,
+    encoding: SignalEncoding,
+    time_table: &[Time],
+    start: u64,
+    end: u64,
+) -> SignalStatistics {
+    let one_bit = matches!(encoding, SignalEncoding::BitVector(width) if width.get() == 1);
+    let range = window_range(signal, time_table, start, end);
+    let changes: Vec<(u64, SignalValue)> = iter_range(signal, range)
+        .map(|(time_idx, value)| (time_table[time_idx as usize], value))
+        .filter(|(time, _)| *time < end)
+        .collect();
+
+    let mut stats = SignalStatistics {
+        transitions: 0,
+        high_time: None,
+        low_time: None,
+        duty_cycle: None,
+        unknown_time: 0,
+        distinct_values: 0,
+        min: None,
+        max: None,
+        mean: None,
+        longest_stable: None,
+    };
+    let mut distinct = HashSet::new();
+    let (mut high_time, mut low_time) = (0, 0);
+    let (mut weighted_sum, mut numeric_time) = (0.0, 0u64);
+    // Start of the span holding the current value, which may cover several equal changes
+    let mut stable_start = start;
+
+    for (i, (time, value)) in changes.iter().enumerate() {
+        let span_start = (*time).max(start);
+        let span_end = changes.get(i + 1).map_or(end, |(next, _)| (*next).max(start));
+        let duration = span_end - span_start;
+
+        let previous = i.checked_sub(1).map(|p| &changes[p].1);
+        if previous.is_none_or(|previous| !value::same_value(previous, value)) {
+            if *time > start && previous.is_some() {
+                stats.transitions += 1;
+            }
+            stable_start = span_start;
+        }
+        if changes.get(i + 1).is_none_or(|(_, next)| !value::same_value(value, next)) {
+            let longest = stats.longest_stable.as_ref().map_or(0, |interval| interval.end - interval.start);
+            if span_end - stable_start > longest {
+                stats.longest_stable = Some(StableInterval { start: stable_start, end: span_end, value: value.to_string() });
+            }
+        }
+        if duration == 0 {
+            continue;
+        }
+
+        distinct.insert(value.to_string());
+        let states = value::bit_states(value);
+        if states.as_ref().is_some_and(|states| states.iter().any(|state| value::state_to_bit(*state).is_none())) {
+            stats.unknown_time += duration;
+        }
+        if let Some([state]) = states.as_deref() {
+            match value::state_to_bit(*state) {
+                Some(true) => high_time += duration,
+                Some(false) => low_time += duration,
+                None => {}
+            }
+        }
+        if let Some(number) = value::to_f64(value) {
+            stats.min = Some(stats.min.map_or(number, |min| min.min(number)));
+            stats.max = Some(stats.max.map_or(number, |max| max.max(number)));
+            weighted_sum += number * duration as f64;
+            numeric_time += duration;
+        }
+    }
+
+    if one_bit {
+        stats.high_time = Some(high_time);
+        stats.low_time = Some(low_time);
+        stats.duty_cycle = (high_time + low_time > 0).then(|| high_time as f64 / (high_time + low_time) as f64);
+    }
+    stats.distinct_values = distinct.len();
+    stats.mean = (numeric_time > 0).then(|| weighted_sum / numeric_time as f64);
+    stats
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use wellen::Time;
+
+use crate::changes::{first_change_at_or_after, iter_range, value_at, SignalData};
+use crate::value;
+
+/// Number of entries folded into one entry of the next pyramid level.
+const FANOUT: usize = 16;
+
+#[derive(Serialize, Deserialize)]
+pub struct SummaryBucket {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    /// Number of changes with a timestamp inside `[start, end)`.
+    pub(crate) transitions: usize,
+    /// True when the value changes somewhere after the start of the bucket.
+    pub(crate) busy: bool,
+    /// Value held across the whole bucket. `None` when busy or before the first change.
+    pub(crate) value: Option<String>,
+    /// Range of numeric values seen in the bucket, for bit vectors without unknown bits and reals.
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+}
+
+/// Min/max of the numeric values of a signal over blocks of changes, at several
+/// resolutions, so the range of any span of changes is found in logarithmic time.
+pub struct MinMaxPyramid {
+    levels: Vec<Vec<Option<(f64, f64)>>>,
+}
+
+impl MinMaxPyramid {
+    pub(crate) fn build(signal: SignalData) -> Self {
+        let raw: Vec<Option<f64>> = iter_range(signal, 0..signal.time_indices().len())
+            .map(|(_, v)| value::to_f64(&v))
+            .collect();
+
+        let mut levels = Vec::new();
+        let mut current: Vec<Option<(f64, f64)>> = raw
+            .chunks(FANOUT)
+            .map(|chunk| chunk.iter().fold(None, |acc, v| merge(acc, v.map(|v| (v, v)))))
+            .collect();
+        while current.len() > 1 {
+            let next = current
+                .chunks(FANOUT)
+                .map(|chunk| chunk.iter().fold(None, |acc, v| merge(acc, *v)))
+                .collect();
+            levels.push(current);
+            current = next;
+        }
+        levels.push(current);
+        MinMaxPyramid { levels }
+    }
+
+    /// Min and max over the changes with index in `lo..hi`.
+    fn range(&self, signal: SignalData, mut lo: usize, mut hi: usize) -> Option<(f64, f64)> {
+        let mut acc = None;
+        // Peel unaligned entries at both ends of the range, then move up one level
+        let mut level: Option<usize> = None;
+        loop {
+            let get = |index: usize| match level {
+                None => value_at(signal, index).and_then(|v| value::to_f64(&v)).map(|v| (v, v)),
+                Some(level) => self.levels[level][index],
+            };
+            while lo < hi && !lo.is_multiple_of(FANOUT) {
+                acc = merge(acc, get(lo));
+                lo += 1;
+            }
+            while lo < hi && !hi.is_multiple_of(FANOUT) {
+                hi -= 1;
+                acc = merge(acc, get(hi));
+            }
+            if lo >= hi {
+                return acc;
+            }
+            let next_level = level.map_or(0, |l| l + 1);
+            if next_level == self.levels.len() {
+                for index in lo..hi {
+                    acc = merge(acc, get(index));
+                }
+                return acc;
+            }
+            lo /= FANOUT;
+            hi /= FANOUT;
+            level = Some(next_level);
+        }
+    }
+}
+
+fn merge(a: Option<(f64, f64)>, b: Option<(f64, f64)>) -> Option<(f64, f64)> {
+    match (a, b) {
+        (Some((a_min, a_max)), Some((b_min, b_max))) => Some((a_min.min(b_min), a_max.max(b_max))),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Splits `[start, end)` into `buckets` equal spans and summarises the signal in each.
+pub(crate) fn summarize(
+    signal: SignalData,
+    time_table: &[Time],
+    pyramid: Option<&MinMaxPyramid>,
+    start: u64,
+    end: u64,
+    buckets: usize,
+) -> Vec<SummaryBucket> {
+    let span = (end - start) as u128;
+    let boundary = |i: usize| start + (span * i as u128 / buckets as u128) as u64;
+    let time_of = |index: usize| time_table[signal.time_indices()[index] as usize];
+
+    let mut out = Vec::with_capacity(buckets);
+    let mut lo = first_change_at_or_after(signal.time_indices(), time_table, start);
+    for i in 0..buckets {
+        let (bucket_start, bucket_end) = (boundary(i), boundary(i + 1));
+        let hi = lo + first_change_at_or_after(&signal.time_indices()[lo..], time_table, bucket_end);
+
+        let transitions = hi - lo;
+        // A change exactly at the bucket start is visible as the bucket's value
+        let busy = transitions > 1 || (transitions == 1 && time_of(lo) > bucket_start);
+        // The value held when the bucket starts is the change just before it
+        let first_visible = if transitions > 0 && time_of(lo) == bucket_start { lo } else { lo.saturating_sub(1) };
+        let has_value = first_visible < hi;
+
+        let value = if !busy && has_value {
+            value_at(signal, first_visible).map(|v| v.to_string())
+        } else {
+            None
+        };
+        let range = match (has_value, pyramid) {
+            (true, Some(pyramid)) => pyramid.range(signal, first_visible, hi),
+            _ => None,
+        };
+
+        out.push(SummaryBucket {
+            start: bucket_start,
+            end: bucket_end,
+            transitions,
+            busy,
+            value,
+            min: range.map(|(min, _)| min),
+            max: range.map(|(_, max)| max),
+        });
+        lo = hi;
+    }
+    out
+}
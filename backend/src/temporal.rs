@@ -0,0 +1,241 @@
+//! Searches for times where a condition over several signals holds. Conditions are
+//! expressions (see the `expression` module) evaluated along the merged change streams
+//! of the signals they use.
+
+use serde::{Deserialize, Serialize};
+use wellen::{Time, TimeTableIdx};
+
+use crate::changes::SignalData;
+use crate::evaluate;
+use crate::expression::Expression;
+use crate::logic;
+use crate::value::STATE_1;
+
+/// Number of evaluation steps between two checks for cancellation.
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TemporalCondition {
+    /// Intervals during which `expression` is true (1).
+    Holds { expression: String },
+    /// Times where `trigger` becomes true and `response` is not true at any point up to
+    /// `within` later: rising edges of `clock` when given, time units otherwise.
+    Unanswered {
+        trigger: String,
+        response: String,
+        within: u64,
+        #[serde(default)]
+        clock: Option<String>,
+    },
+}
+
+impl TemporalCondition {
+    /// Every expression the condition uses, in the order `search` expects them.
+    pub(crate) fn expressions(&self) -> Vec<&str> {
+        match self {
+            TemporalCondition::Holds { expression } => vec![expression],
+            TemporalCondition::Unanswered { trigger, response, clock, .. } => {
+                let mut out = vec![trigger.as_str(), response.as_str()];
+                out.extend(clock.as_deref());
+                out
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemporalQuery {
+    pub condition: TemporalCondition,
+    /// Time to search from; the `next_start` of the previous page when paginating.
+    #[serde(default)]
+    pub start: u64,
+    /// No match starts after this time.
+    #[serde(default)]
+    pub end: Option<u64>,
+    /// The page stops after this many matches.
+    pub max_results: usize,
+    /// Lets `cancel_temporal_search` stop the search while it runs.
+    #[serde(default)]
+    pub search_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct TemporalMatch {
+    pub(crate) start: u64,
+    /// For `holds`, when the condition stops holding; for `unanswered`, the deadline for
+    /// the response. `None` when the trace ends first.
+    pub(crate) end: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemporalSearchResults {
+    pub(crate) matches: Vec<TemporalMatch>,
+    /// Where the next page starts, `None` once the search has covered the whole range.
+    pub(crate) next_start: Option<u64>,
+}
+
+/// An expression together with the changes of its operands.
+pub(crate) struct BoundExpression<'e, 'a> {
+    pub(crate) expression: &'e Expression,
+    pub(crate) sources: Vec<SignalData<'a>>,
+}
+
+impl BoundExpression<'_, '_> {
+    /// Truth value (0, 1 or x) after each change of it, from `from` on.
+    fn truth_changes(&self, from: TimeTableIdx, cancelled: &dyn Fn() -> bool) -> Result<Vec<(TimeTableIdx, u8)>, String> {
+        let mut out: Vec<(TimeTableIdx, u8)> = Vec::new();
+        for (step, (time_idx, value)) in evaluate::stream(self.expression, &self.sources, from).enumerate() {
+            check_cancelled(step, cancelled)?;
+            let truth = logic::truth(&value);
+            if out.last().is_none_or(|(_, last)| *last != truth) {
+                out.push((time_idx, truth));
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn check_cancelled(step: usize, cancelled: &dyn Fn() -> bool) -> Result<(), String> {
+    if step.is_multiple_of(CANCEL_CHECK_INTERVAL) && cancelled() {
+        return Err("Search cancelled".to_string());
+    }
+    Ok(())
+}
+
+/// Runs one page of a search. `bound` holds the condition's expressions in the order of
+/// `TemporalCondition::expressions`.
+pub(crate) fn search(
+    query: &TemporalQuery,
+    bound: &[BoundExpression],
+    time_table: &[Time],
+    cancelled: &dyn Fn() -> bool,
+) -> Result<TemporalSearchResults, String> {
+    if query.max_results == 0 {
+        return Err("max_results must be positive".to_string());
+    }
+    let from = time_table.partition_point(|t| *t < query.start) as TimeTableIdx;
+    match &query.condition {
+        TemporalCondition::Holds { .. } => holds(query, &bound[0], time_table, from, cancelled),
+        TemporalCondition::Unanswered { within, .. } => {
+            unanswered(query, bound, *within, time_table, from, cancelled)
+        }
+    }
+}
+
+fn holds(
+    query: &TemporalQuery,
+    condition: &BoundExpression,
+    time_table: &[Time],
+    from: TimeTableIdx,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<TemporalSearchResults, String> {
+    let mut matches = Vec::new();
+    let mut open: Option<Time> = None;
+    for (step, (time_idx, value)) in evaluate::stream(condition.expression, &condition.sources, from).enumerate() {
+        check_cancelled(step, cancelled)?;
+        let time = time_table[time_idx as usize];
+        let past_end = query.end.is_some_and(|end| time > end);
+        match (open, logic::truth(&value) == STATE_1) {
+            (None, _) if past_end => return Ok(TemporalSearchResults { matches, next_start: None }),
+            (None, true) => open = Some(time),
+            (Some(start), false) => {
+                matches.push(TemporalMatch { start, end: Some(time) });
+                open = None;
+                if matches.len() == query.max_results {
+                    return Ok(TemporalSearchResults { matches, next_start: Some(time) });
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = open {
+        matches.push(TemporalMatch { start, end: None });
+    }
+    Ok(TemporalSearchResults { matches, next_start: None })
+}
+
+fn unanswered(
+    query: &TemporalQuery,
+    bound: &[BoundExpression],
+    within: u64,
+    time_table: &[Time],
+    from: TimeTableIdx,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<TemporalSearchResults, String> {
+    let (trigger, response, clock) = (&bound[0], &bound[1], bound.get(2));
+    if clock.is_some() && within == 0 {
+        return Err("within must be at least one clock cycle".to_string());
+    }
+
+    let responses = response.truth_changes(0, cancelled)?;
+    let clock_edges: Option<Vec<TimeTableIdx>> = match clock {
+        Some(clock) => {
+            let changes = clock.truth_changes(0, cancelled)?;
+            Some(
+                changes
+                    .windows(2)
+                    .filter(|pair| pair[1].1 == STATE_1 && pair[0].1 != STATE_1)
+                    .map(|pair| pair[1].0)
+                    .collect(),
+            )
+        }
+        None => None,
+    };
+
+    // Time table index of the last moment a trigger at `time_idx` may be answered
+    let deadline = |time_idx: TimeTableIdx| -> Option<TimeTableIdx> {
+        match &clock_edges {
+            Some(edges) => {
+                // An edge count beyond the trace's edges is past its end
+                let next = edges.partition_point(|edge| *edge <= time_idx);
+                let last = usize::try_from(within - 1).ok().and_then(|after| next.checked_add(after))?;
+                edges.get(last).copied()
+            }
+            None => {
+                let limit = time_table[time_idx as usize].checked_add(within)?;
+                if limit > *time_table.last()? {
+                    return None;
+                }
+                Some(time_table.partition_point(|t| *t <= limit) as TimeTableIdx - 1)
+            }
+        }
+    };
+    let answered = |time_idx: TimeTableIdx, deadline: Option<TimeTableIdx>| {
+        // The response holding at the trigger counts, as does any change up to the deadline
+        let first = responses.partition_point(|(idx, _)| *idx <= time_idx).saturating_sub(1);
+        responses[first..]
+            .iter()
+            .take_while(|(idx, _)| deadline.is_none_or(|deadline| *idx <= deadline))
+            .any(|(_, truth)| *truth == STATE_1)
+    };
+
+    // Start one index early so that a rise exactly at `from` is seen as one
+    let mut matches = Vec::new();
+    let mut previous: Option<u8> = None;
+    let stream = evaluate::stream(trigger.expression, &trigger.sources, from.saturating_sub(1));
+    for (step, (time_idx, value)) in stream.enumerate() {
+        check_cancelled(step, cancelled)?;
+        let truth = logic::truth(&value);
+        let rose = truth == STATE_1 && previous.is_some_and(|previous| previous != STATE_1);
+        previous = Some(truth);
+        if !rose || time_idx < from {
+            continue;
+        }
+        let time = time_table[time_idx as usize];
+        if query.end.is_some_and(|end| time > end) {
+            break;
+        }
+        let deadline = deadline(time_idx);
+        if !answered(time_idx, deadline) {
+            matches.push(TemporalMatch {
+                start: time,
+                end: deadline.map(|idx| time_table[idx as usize]),
+            });
+            if matches.len() == query.max_results {
+                return Ok(TemporalSearchResults { matches, next_start: Some(time + 1) });
+            }
+        }
+    }
+    Ok(TemporalSearchResults { matches, next_start: None })
+}
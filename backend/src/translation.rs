@@ -0,0 +1,145 @@
+//! Tables mapping values to labels, so that a state machine reads `S_WAIT_ACK` rather
+//! than `0110`. Tables come from GTKWave filter files, JSON files, or the enum types of
+//! VHDL signals in GHW files.
+//!
+//! Filter files hold one `value label` pair per line, with `#` starting a comment and an
+//! optional GTKWave `?color?` prefix on the label. JSON files hold an object from values to
+//! labels. Values are hex, as GTKWave shows them by default, unless written as Verilog
+//! literals (`4'b0110`) or with a `0x`, `0o` or `0b` prefix. A value matches whatever the
+//! width of the signal, so `6` matches both `0110` and `00000110`.
+//!
+//! Decoders that a table cannot express plug in as a [`ValueTranslator`] instead, such as
+//! the external processes of the native app.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use wellen::SignalValue;
+
+use crate::expression::literal_width;
+use crate::logic;
+use crate::value::{bit_states, parse_literal, STATE_0};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationFormat {
+    /// GTKWave translate filter file.
+    Filter,
+    /// `{"value": "label", ...}`.
+    Json,
+}
+
+/// Turns formatted values into labels. Attached to a signal, it takes precedence over any
+/// translation table. Values it fails on are shown formatted.
+pub trait ValueTranslator: Send {
+    fn translate(&mut self, value: &str) -> Result<String, String>;
+}
+
+pub(crate) struct TranslationTable {
+    /// Keyed by four-state values without leading zeros.
+    labels: HashMap<Vec<u8>, String>,
+}
+
+impl TranslationTable {
+    pub(crate) fn parse(text: &str, format: TranslationFormat) -> Result<Self, String> {
+        let entries: Vec<(String, String)> = match format {
+            TranslationFormat::Filter => {
+                let mut entries = Vec::new();
+                for (number, line) in text.lines().enumerate() {
+                    let line = line.split('#').next().unwrap_or_default().trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let (value, label) = line
+                        .split_once(char::is_whitespace)
+                        .ok_or_else(|| format!("Line {}: expected a value and a label", number + 1))?;
+                    entries.push((value.to_string(), strip_color(label.trim()).to_string()));
+                }
+                entries
+            }
+            TranslationFormat::Json => serde_json::from_str::<BTreeMap<String, String>>(text)
+                .map_err(|e| format!("Invalid translation file: {}", e))?
+                .into_iter()
+                .collect(),
+        };
+
+        let mut labels = HashMap::new();
+        for (value, label) in entries {
+            let literal = if value.contains('\'') || value.get(..2).is_some_and(|p| ["0x", "0o", "0b"].contains(&p.to_ascii_lowercase().as_str())) {
+                value
+            } else {
+                format!("'h{}", value)
+            };
+            labels.insert(key(parse_literal(&literal, literal_width(&literal)?)?), label);
+        }
+        Ok(TranslationTable { labels })
+    }
+
+    /// Table for the literals of an enum type, given as wellen lists them: a binary
+    /// encoding and a name each.
+    pub(crate) fn from_enum(literals: &[(&str, &str)]) -> Result<Self, String> {
+        let mut labels = HashMap::new();
+        for (bits, name) in literals {
+            let literal = format!("'b{}", bits);
+            labels.insert(key(parse_literal(&literal, bits.len() as u32)?), name.to_string());
+        }
+        Ok(TranslationTable { labels })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// The label of a bit-vector value, `None` when the table does not list it.
+    pub(crate) fn translate(&self, value: &SignalValue) -> Option<&str> {
+        let states = bit_states(value)?.into_iter().map(logic::to_four_state).collect();
+        self.labels.get(&key(states)).map(String::as_str)
+    }
+}
+
+/// Drops leading zeros so that values match across widths.
+fn key(mut states: Vec<u8>) -> Vec<u8> {
+    let zeros = states.iter().take_while(|state| **state == STATE_0).count();
+    states.drain(..zeros.min(states.len() - 1));
+    states
+}
+
+/// GTKWave labels may start with `?color?`.
+fn strip_color(label: &str) -> &str {
+    label
+        .strip_prefix('?')
+        .and_then(|rest| rest.split_once('?'))
+        .map_or(label, |(_, label)| label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_and_json_tables() {
+        let filter = "# states\n00 IDLE\n1 ?red?S_WAIT_ACK\n4'b001x  PARTIAL # unknown low bit\n\n";
+        let table = TranslationTable::parse(filter, TranslationFormat::Filter).unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b0000], 4)), Some("IDLE"));
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b0001], 8)), Some("S_WAIT_ACK"));
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b0010], 4)), None);
+        assert_eq!(
+            TranslationTable::parse("1\n", TranslationFormat::Filter).err().as_deref(),
+            Some("Line 1: expected a value and a label")
+        );
+
+        let json = r#"{"0b0110": "S_WAIT_ACK", "a": "TEN"}"#;
+        let table = TranslationTable::parse(json, TranslationFormat::Json).unwrap();
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b0110], 4)), Some("S_WAIT_ACK"));
+        assert_eq!(table.translate(&SignalValue::Binary(&[10], 8)), Some("TEN"));
+        assert!(TranslationTable::parse("[1]", TranslationFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_enum_table() {
+        let table = TranslationTable::from_enum(&[("00", "idle"), ("01", "busy"), ("10", "done")]).unwrap();
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b01], 2)), Some("busy"));
+        assert_eq!(table.translate(&SignalValue::Binary(&[0b11], 2)), None);
+    }
+}
@@ -0,0 +1,180 @@
+use wellen::SignalValue;
+
+/// Per-bit state codes in wellen's order.
+pub(crate) const STATE_0: u8 = 0;
+pub(crate) const STATE_1: u8 = 1;
+pub(crate) const STATE_X: u8 = 2;
+pub(crate) const STATE_Z: u8 = 3;
+pub(crate) const STATE_H: u8 = 4;
+pub(crate) const STATE_L: u8 = 7;
+
+/// Decodes the state of every bit of a bit-vector value, msb first.
+/// Returns `None` for real and string values.
+pub(crate) fn bit_states(value: &SignalValue) -> Option<Vec<u8>> {
+    let (data, bits, state_bits) = match value {
+        SignalValue::Binary(data, bits) => (*data, *bits, 1),
+        SignalValue::FourValue(data, bits) => (*data, *bits, 2),
+        SignalValue::NineValue(data, bits) => (*data, *bits, 4),
+        SignalValue::String(_) | SignalValue::Real(_) => return None,
+    };
+    let per_byte = 8 / state_bits;
+    let mask = (1u8 << state_bits) - 1;
+
+    let mut out = Vec::with_capacity(bits as usize);
+    let mut bytes = data.iter();
+    // The first byte only holds the leftover states when the width is not a multiple
+    let leading = bits % per_byte;
+    if leading > 0 {
+        let byte = bytes.next()?;
+        for ii in (0..leading).rev() {
+            out.push((byte >> (ii * state_bits)) & mask);
+        }
+    }
+    for byte in bytes {
+        for ii in (0..per_byte).rev() {
+            out.push((byte >> (ii * state_bits)) & mask);
+        }
+    }
+    Some(out)
+}
+
+/// Resolves a state to a plain bit, treating the weak VHDL levels as strong ones.
+/// Returns `None` for x, z and the other unknown states.
+pub(crate) fn state_to_bit(state: u8) -> Option<bool> {
+    match state {
+        STATE_0 | STATE_L => Some(false),
+        STATE_1 | STATE_H => Some(true),
+        _ => None,
+    }
+}
+
+/// Interprets a value as a number: reals as-is, bit vectors as unsigned integers.
+/// Returns `None` for strings and for vectors with any unknown bit.
+pub(crate) fn to_f64(value: &SignalValue) -> Option<f64> {
+    match value {
+        SignalValue::Real(value) => Some(*value),
+        SignalValue::String(_) => None,
+        // Two-state data is a plain big-endian number
+        SignalValue::Binary(data, _) => Some(data.iter().fold(0.0, |acc, byte| acc * 256.0 + *byte as f64)),
+        _ => bit_states(value)?
+            .into_iter()
+            .try_fold(0.0, |acc, state| Some(acc * 2.0 + if state_to_bit(state)? { 1.0 } else { 0.0 })),
+    }
+}
+
+/// True when two values hold the same states, number or text.
+pub(crate) fn same_value(a: &SignalValue, b: &SignalValue) -> bool {
+    match (a, b) {
+        (SignalValue::Real(a), SignalValue::Real(b)) => a == b,
+        (SignalValue::String(a), SignalValue::String(b)) => a == b,
+        _ => bit_states(a).is_some() && bit_states(a) == bit_states(b),
+    }
+}
+
+/// Parses a literal into bit states (msb first) of the given width. Accepts Verilog
+/// literals (`8'hFF`, `'b10x1`, `4'sd3`), `0x`/`0o`/`0b` prefixes and plain decimals.
+/// Underscores are ignored. Values are zero-extended or truncated to `width`, except that
+/// a leading x or z digit extends with x or z as in Verilog.
+pub(crate) fn parse_literal(text: &str, width: u32) -> Result<Vec<u8>, String> {
+    let literal: String = text.trim().chars().filter(|c| *c != '_').collect();
+    let invalid = || format!("Invalid literal: {}", text);
+
+    let (base, digits) = if let Some(tick) = literal.find('\'') {
+        let rest = literal[tick + 1..].trim_start_matches(['s', 'S']);
+        let mut chars = rest.chars();
+        let base = match chars.next().map(|c| c.to_ascii_lowercase()) {
+            Some('b') => 2,
+            Some('o') => 8,
+            Some('d') => 10,
+            Some('h') => 16,
+            _ => return Err(invalid()),
+        };
+        (base, chars.as_str().to_string())
+    } else if let Some(hex) = literal.strip_prefix("0x").or_else(|| literal.strip_prefix("0X")) {
+        (16, hex.to_string())
+    } else if let Some(octal) = literal.strip_prefix("0o").or_else(|| literal.strip_prefix("0O")) {
+        (8, octal.to_string())
+    } else if let Some(binary) = literal.strip_prefix("0b").or_else(|| literal.strip_prefix("0B")) {
+        (2, binary.to_string())
+    } else {
+        (10, literal.clone())
+    };
+    if digits.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut states = Vec::new();
+    if base == 10 {
+        let value: u128 = digits.parse().map_err(|_| invalid())?;
+        for i in (0..128).rev() {
+            states.push(((value >> i) & 1) as u8);
+        }
+    } else {
+        let digit_bits = match base {
+            2 => 1,
+            8 => 3,
+            _ => 4,
+        };
+        for c in digits.chars() {
+            match c.to_ascii_lowercase() {
+                'x' => states.extend(std::iter::repeat_n(STATE_X, digit_bits)),
+                'z' | '?' => states.extend(std::iter::repeat_n(STATE_Z, digit_bits)),
+                c => {
+                    let digit = c.to_digit(base).ok_or_else(invalid)?;
+                    for i in (0..digit_bits).rev() {
+                        states.push(((digit >> i) & 1) as u8);
+                    }
+                }
+            }
+        }
+    }
+
+    let width = width as usize;
+    if states.len() >= width {
+        Ok(states.split_off(states.len() - width))
+    } else {
+        let fill = match states[0] {
+            STATE_X | STATE_Z => states[0],
+            _ => STATE_0,
+        };
+        let mut out = vec![fill; width - states.len()];
+        out.extend(states);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_states_and_numeric_value() {
+        // 3 bits of four-state data: the first byte holds only the leftover states
+        let value = SignalValue::FourValue(&[0b0000_0110], 3);
+        assert_eq!(bit_states(&value).unwrap(), vec![0, 1, 2]);
+        assert_eq!(to_f64(&value), None);
+
+        let value = SignalValue::FourValue(&[0b0001, 0b0100_0001], 6);
+        assert_eq!(bit_states(&value).unwrap(), vec![0, 1, 1, 0, 0, 1]);
+        assert_eq!(to_f64(&value), Some(0b011001 as f64));
+
+        let value = SignalValue::Binary(&[0b101, 0xff], 11);
+        assert_eq!(to_f64(&value), Some(0b101_1111_1111 as f64));
+
+        assert_eq!(to_f64(&SignalValue::Real(1.5)), Some(1.5));
+        assert_eq!(to_f64(&SignalValue::String("idle")), None);
+    }
+
+    #[test]
+    fn test_parse_literal() {
+        assert_eq!(parse_literal("8'hA5", 8).unwrap(), vec![1, 0, 1, 0, 0, 1, 0, 1]);
+        assert_eq!(parse_literal("'b101", 5).unwrap(), vec![0, 0, 1, 0, 1]);
+        assert_eq!(parse_literal("0x1_f", 4).unwrap(), vec![1, 1, 1, 1]);
+        assert_eq!(parse_literal("6", 4).unwrap(), vec![0, 1, 1, 0]);
+        assert_eq!(parse_literal("4'sd3", 4).unwrap(), vec![0, 0, 1, 1]);
+        assert_eq!(parse_literal("'bx1", 4).unwrap(), vec![STATE_X, STATE_X, STATE_X, 1]);
+        assert_eq!(parse_literal("'hz", 2).unwrap(), vec![STATE_Z, STATE_Z]);
+        assert!(parse_literal("8'hG1", 8).is_err());
+        assert!(parse_literal("", 8).is_err());
+    }
+}
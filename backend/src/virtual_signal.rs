@@ -0,0 +1,120 @@
+use std::num::NonZeroU32;
+
+use wellen::{ScopeRef, SignalEncoding, SignalValue, TimeTableIdx};
+
+use crate::changes::SignalData;
+use crate::evaluate;
+use crate::expression::Expression;
+
+/// A signal computed from an expression over other signals. Its changes are evaluated
+/// on the first query and then kept in memory next to the file's own signals.
+pub struct VirtualSignal {
+    pub(crate) name: String,
+    pub(crate) expression: String,
+    /// Scope the signal is listed in, `None` for the top level.
+    pub(crate) scope: Option<ScopeRef>,
+    /// Declared `[msb:lsb]` range, for buses grouped from single-bit vars.
+    pub(crate) index: Option<(i64, i64)>,
+    pub(crate) var_type: &'static str,
+    pub(crate) direction: &'static str,
+    width: u32,
+    changes: Option<Changes>,
+}
+
+/// Evaluated changes of a virtual signal.
+pub(crate) struct Changes {
+    time_indices: Vec<TimeTableIdx>,
+    /// Four-state values packed as wellen does, `ceil(width / 4)` bytes per change.
+    data: Vec<u8>,
+}
+
+impl Changes {
+    /// Evaluates `expression` at every change of its operands. `sources` holds the changes
+    /// of each operand, in the order of `Expression::operands`.
+    pub(crate) fn evaluate(expression: &Expression, sources: &[SignalData]) -> Self {
+        let bytes_per_value = expression.width().div_ceil(4);
+
+        let mut time_indices = Vec::new();
+        let mut data = Vec::new();
+        let mut previous: Option<Vec<u8>> = None;
+        for (time_idx, result) in evaluate::stream(expression, sources, 0) {
+            if previous.as_ref() != Some(&result) {
+                time_indices.push(time_idx);
+                pack(&result, bytes_per_value, &mut data);
+                previous = Some(result);
+            }
+        }
+        Changes { time_indices, data }
+    }
+}
+
+impl VirtualSignal {
+    /// A top-level virtual signal of `width` bits, evaluated later with `set_changes`.
+    pub(crate) fn new(name: String, expression: String, width: usize) -> Self {
+        VirtualSignal {
+            name,
+            expression,
+            scope: None,
+            index: None,
+            var_type: "virtual",
+            direction: "implicit",
+            width: width as u32,
+            changes: None,
+        }
+    }
+
+    pub(crate) fn is_evaluated(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    pub(crate) fn set_changes(&mut self, changes: Changes) {
+        self.changes = Some(changes);
+    }
+
+    pub(crate) fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub(crate) fn encoding(&self) -> SignalEncoding {
+        SignalEncoding::BitVector(NonZeroU32::new(self.width).unwrap())
+    }
+
+    /// Empty until the signal is evaluated.
+    pub(crate) fn time_indices(&self) -> &[TimeTableIdx] {
+        self.changes.as_ref().map_or(&[], |changes| &changes.time_indices)
+    }
+
+    pub(crate) fn value(&self, index: usize) -> SignalValue<'_> {
+        let bytes_per_value = (self.width as usize).div_ceil(4);
+        let start = index * bytes_per_value;
+        let data = &self.changes.as_ref().expect("virtual signal is evaluated").data;
+        SignalValue::FourValue(&data[start..start + bytes_per_value], self.width)
+    }
+}
+
+/// Appends four-state codes, two bits each, msb first and right-aligned in the bytes.
+fn pack(states: &[u8], bytes_per_value: usize, out: &mut Vec<u8>) {
+    let row_start = out.len();
+    out.resize(row_start + bytes_per_value, 0);
+    let padding = bytes_per_value * 4 - states.len();
+    for (i, state) in states.iter().enumerate() {
+        let slot = padding + i;
+        out[row_start + slot / 4] |= state << (2 * (3 - slot % 4));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::bit_states;
+
+    #[test]
+    fn test_pack_round_trips_through_bit_states() {
+        for states in [vec![1, 0, 2], vec![3, 1, 0, 1, 2], vec![1, 1, 1, 1]] {
+            let mut data = Vec::new();
+            pack(&states, states.len().div_ceil(4), &mut data);
+            let value = SignalValue::FourValue(&data, states.len() as u32);
+            assert_eq!(bit_states(&value).unwrap(), states);
+        }
+    }
+}
@@ -1,12 +1,44 @@
+mod translator;
+
 use backend::add_file;
 use backend::{
-    get_files as backend_get_files, get_hierarchy as backend_get_hierarchy,
-    get_signal_changes as backend_get_signal_changes, remove_file as backend_remove_file,
+    add_bus_fields as backend_add_bus_fields,
+    add_translation_table as backend_add_translation_table,
+    add_virtual_signal as backend_add_virtual_signal, assemble_bus as backend_assemble_bus,
+    cancel_temporal_search as backend_cancel_temporal_search,
+    check_assertion as backend_check_assertion, cycles_to_times as backend_cycles_to_times,
+    detect_clocks as backend_detect_clocks, export_event_list_csv as backend_export_event_list_csv,
+    find_signal_change as backend_find_signal_change, get_activity_map as backend_get_activity_map,
+    get_analog_samples as backend_get_analog_samples, get_changes_near as backend_get_changes_near,
+    get_clock_info as backend_get_clock_info, get_cycle_axis as backend_get_cycle_axis,
+    get_event_list as backend_get_event_list, get_files as backend_get_files,
+    get_hierarchy as backend_get_hierarchy, get_scope_children as backend_get_scope_children,
+    get_signal_changes as backend_get_signal_changes,
+    get_signal_changes_batch as backend_get_signal_changes_batch,
+    get_signal_changes_packed as backend_get_signal_changes_packed,
+    get_signal_statistics as backend_get_signal_statistics,
+    get_signal_summary as backend_get_signal_summary,
+    get_translation_tables as backend_get_translation_tables,
+    get_values_at as backend_get_values_at, get_waveform_info as backend_get_waveform_info,
+    remove_file as backend_remove_file, remove_virtual_signal as backend_remove_virtual_signal,
+    sample_on_clock as backend_sample_on_clock, search_signals as backend_search_signals,
+    search_temporal as backend_search_temporal, set_interpolation as backend_set_interpolation,
+    set_signal_translation as backend_set_signal_translation,
+    set_signal_translator as backend_set_signal_translator,
+    times_to_cycles as backend_times_to_cycles,
+};
+use backend::{
+    ActivityMap, ActivitySource, AnalogWindow, AssertionReport, BusField, ClockEdge, ClockInfo,
+    CycleAxisInfo, EventListPage, EventListQuery, HierarchyRoot, HierarchyVar, Interpolation,
+    NavigationDirection, NavigationTarget, NearbyChanges, SampleQuery, SampledTable, ScopeChildren,
+    SearchQuery, SearchResults, SignalChange, SignalStatistics, SummaryBucket, TemporalQuery,
+    TemporalSearchResults, TranslationFormat, ValueAt, ValueFormat, ValueTranslator, WaveformInfo,
 };
-use backend::{HierarchyRoot, SignalChange};
 use tauri_plugin_store::StoreExt;
 use tauri_plugin_cli::CliExt;
+use std::collections::BTreeMap;
 use std::sync::Mutex;
+use translator::ProcessTranslator;
 
 const OPENED_FILES_KEY: &str = "opened_files";
 const DOCK_STATE_KEY: &str = "dock_state";
@@ -60,14 +92,276 @@ fn get_hierarchy(filename: String) -> Result<HierarchyRoot, String> {
     backend_get_hierarchy(filename)
 }
 
+#[tauri::command]
+fn get_scope_children(
+    filename: String,
+    scope_ref: Option<usize>,
+    offset: usize,
+    limit: usize,
+) -> Result<ScopeChildren, String> {
+    backend_get_scope_children(filename, scope_ref, offset, limit)
+}
+
+#[tauri::command]
+fn search_signals(
+    filename: String,
+    query: SearchQuery,
+    offset: usize,
+    limit: usize,
+) -> Result<SearchResults, String> {
+    backend_search_signals(filename, query, offset, limit)
+}
+
+#[tauri::command]
+fn get_waveform_info(filename: String) -> Result<WaveformInfo, String> {
+    backend_get_waveform_info(filename)
+}
+
 #[tauri::command]
 fn get_signal_changes(
     filename: String,
     signal_id: usize,
     start: u64,
     end: u64,
+    format: Option<ValueFormat>,
 ) -> Result<Vec<SignalChange>, String> {
-    backend_get_signal_changes(filename, signal_id, start, end)
+    backend_get_signal_changes(filename, signal_id, start, end, format)
+}
+
+/// Sent as a raw IPC body so the frontend receives an `ArrayBuffer` instead of JSON.
+#[tauri::command]
+fn get_signal_changes_packed(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+) -> Result<tauri::ipc::Response, String> {
+    backend_get_signal_changes_packed(filename, signal_id, start, end).map(tauri::ipc::Response::new)
+}
+
+#[tauri::command]
+fn get_signal_changes_batch(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: u64,
+    format: Option<ValueFormat>,
+) -> Result<BTreeMap<usize, Vec<SignalChange>>, String> {
+    backend_get_signal_changes_batch(filename, signal_ids, start, end, format)
+}
+
+// Async because the first call loads every 1-bit signal of the file
+#[tauri::command]
+async fn detect_clocks(filename: String) -> Result<Vec<ClockInfo>, String> {
+    backend_detect_clocks(filename)
+}
+
+#[tauri::command]
+fn get_clock_info(filename: String, signal_id: usize) -> Result<Option<ClockInfo>, String> {
+    backend_get_clock_info(filename, signal_id)
+}
+
+#[tauri::command]
+fn get_cycle_axis(filename: String, clock_id: usize, edge: ClockEdge) -> Result<CycleAxisInfo, String> {
+    backend_get_cycle_axis(filename, clock_id, edge)
+}
+
+#[tauri::command]
+fn times_to_cycles(
+    filename: String,
+    clock_id: usize,
+    edge: ClockEdge,
+    times: Vec<u64>,
+) -> Result<Vec<f64>, String> {
+    backend_times_to_cycles(filename, clock_id, edge, times)
+}
+
+#[tauri::command]
+fn cycles_to_times(
+    filename: String,
+    clock_id: usize,
+    edge: ClockEdge,
+    cycles: Vec<f64>,
+) -> Result<Vec<u64>, String> {
+    backend_cycles_to_times(filename, clock_id, edge, cycles)
+}
+
+#[tauri::command]
+fn sample_on_clock(filename: String, query: SampleQuery) -> Result<SampledTable, String> {
+    backend_sample_on_clock(filename, query)
+}
+
+#[tauri::command]
+fn get_event_list(filename: String, query: EventListQuery) -> Result<EventListPage, String> {
+    backend_get_event_list(filename, query)
+}
+
+// Async because exporting a whole trace takes a while
+#[tauri::command]
+async fn export_event_list_csv(
+    filename: String,
+    signal_ids: Vec<usize>,
+    start: u64,
+    end: Option<u64>,
+    format: Option<ValueFormat>,
+) -> Result<String, String> {
+    backend_export_event_list_csv(filename, signal_ids, start, end, format)
+}
+
+// Async because it reads every signal under the scope
+#[tauri::command]
+async fn get_changes_near(
+    filename: String,
+    scope_ref: Option<usize>,
+    time: u64,
+    radius: u64,
+    limit: usize,
+    format: Option<ValueFormat>,
+) -> Result<NearbyChanges, String> {
+    backend_get_changes_near(filename, scope_ref, time, radius, limit, format)
+}
+
+// Async because a scope reads every signal under it
+#[tauri::command]
+async fn get_activity_map(filename: String, source: ActivitySource, buckets: usize) -> Result<ActivityMap, String> {
+    backend_get_activity_map(filename, source, buckets)
+}
+
+#[tauri::command]
+fn get_signal_statistics(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+) -> Result<SignalStatistics, String> {
+    backend_get_signal_statistics(filename, signal_id, start, end)
+}
+
+#[tauri::command]
+fn get_analog_samples(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    max_samples: Option<usize>,
+) -> Result<AnalogWindow, String> {
+    backend_get_analog_samples(filename, signal_id, start, end, max_samples)
+}
+
+#[tauri::command]
+fn set_interpolation(filename: String, signal_id: usize, interpolation: Interpolation) -> Result<(), String> {
+    backend_set_interpolation(filename, signal_id, interpolation)
+}
+
+#[tauri::command]
+fn get_values_at(
+    filename: String,
+    signal_ids: Vec<usize>,
+    time: u64,
+    format: Option<ValueFormat>,
+) -> Result<BTreeMap<usize, ValueAt>, String> {
+    backend_get_values_at(filename, signal_ids, time, format)
+}
+
+// Async so that it runs off the main thread and `cancel_temporal_search` can reach it
+#[tauri::command]
+async fn search_temporal(
+    filename: String,
+    query: TemporalQuery,
+) -> Result<TemporalSearchResults, String> {
+    backend_search_temporal(filename, query)
+}
+
+#[tauri::command]
+fn cancel_temporal_search(search_id: u32) {
+    backend_cancel_temporal_search(search_id)
+}
+
+// Async for the same reason as `search_temporal`: checking a long trace takes a while
+#[tauri::command]
+async fn check_assertion(
+    filename: String,
+    property: String,
+    failures_only: bool,
+) -> Result<AssertionReport, String> {
+    backend_check_assertion(filename, property, failures_only)
+}
+
+#[tauri::command]
+fn add_virtual_signal(filename: String, name: String, expression: String) -> Result<HierarchyVar, String> {
+    backend_add_virtual_signal(filename, name, expression)
+}
+
+#[tauri::command]
+fn add_bus_fields(
+    filename: String,
+    signal_id: usize,
+    fields: Vec<BusField>,
+) -> Result<Vec<HierarchyVar>, String> {
+    backend_add_bus_fields(filename, signal_id, fields)
+}
+
+#[tauri::command]
+fn assemble_bus(filename: String, name: String, signal_ids: Vec<usize>) -> Result<HierarchyVar, String> {
+    backend_assemble_bus(filename, name, signal_ids)
+}
+
+#[tauri::command]
+fn remove_virtual_signal(filename: String, signal_id: usize) -> Result<(), String> {
+    backend_remove_virtual_signal(filename, signal_id)
+}
+
+#[tauri::command]
+fn add_translation_table(
+    filename: String,
+    name: String,
+    content: String,
+    format: TranslationFormat,
+) -> Result<usize, String> {
+    backend_add_translation_table(filename, name, content, format)
+}
+
+#[tauri::command]
+fn get_translation_tables(filename: String) -> Result<Vec<String>, String> {
+    backend_get_translation_tables(filename)
+}
+
+#[tauri::command]
+fn set_signal_translation(filename: String, signal_id: usize, table: Option<String>) -> Result<(), String> {
+    backend_set_signal_translation(filename, signal_id, table)
+}
+
+/// Labels the signal's values with an external process, or detaches it with `None`.
+/// Native only, so it has no counterpart in the wasm backend.
+#[tauri::command]
+fn set_process_translator(filename: String, signal_id: usize, path: Option<String>) -> Result<(), String> {
+    let translator: Option<Box<dyn ValueTranslator>> = match path {
+        Some(path) => Some(Box::new(ProcessTranslator::spawn(&path)?)),
+        None => None,
+    };
+    backend_set_signal_translator(filename, signal_id, translator)
+}
+
+#[tauri::command]
+fn find_signal_change(
+    filename: String,
+    signal_id: usize,
+    from: u64,
+    direction: NavigationDirection,
+    target: NavigationTarget,
+) -> Result<Option<u64>, String> {
+    backend_find_signal_change(filename, signal_id, from, direction, target)
+}
+
+#[tauri::command]
+fn get_signal_summary(
+    filename: String,
+    signal_id: usize,
+    start: u64,
+    end: u64,
+    buckets: usize,
+) -> Result<Vec<SummaryBucket>, String> {
+    backend_get_signal_summary(filename, signal_id, start, end, buckets)
 }
 
 #[tauri::command]
@@ -241,7 +535,39 @@ pub fn run() {
             get_files,
             remove_file,
             get_hierarchy,
+            get_scope_children,
+            search_signals,
+            get_waveform_info,
             get_signal_changes,
+            get_signal_changes_packed,
+            get_signal_changes_batch,
+            get_signal_summary,
+            get_signal_statistics,
+            detect_clocks,
+            get_clock_info,
+            get_cycle_axis,
+            times_to_cycles,
+            cycles_to_times,
+            sample_on_clock,
+            get_event_list,
+            export_event_list_csv,
+            get_changes_near,
+            get_activity_map,
+            get_analog_samples,
+            set_interpolation,
+            get_values_at,
+            find_signal_change,
+            add_virtual_signal,
+            add_bus_fields,
+            assemble_bus,
+            add_translation_table,
+            get_translation_tables,
+            set_signal_translation,
+            set_process_translator,
+            remove_virtual_signal,
+            search_temporal,
+            cancel_temporal_search,
+            check_assertion,
             get_setting,
             set_setting,
             get_all_settings,
@@ -0,0 +1,142 @@
+//! External translator processes, like GTKWave's transaction filter processes: an
+//! executable that reads one formatted value per line on stdin and writes its label as one
+//! line on stdout. Lets in-house decoders, written in any language, label values without
+//! rebuilding the app.
+
+use backend::ValueTranslator;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// How long a translator may take to label one value, so that one that stops answering
+/// does not hang the views showing its signal.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct ProcessTranslator {
+    path: String,
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines of stdout, read on a thread of their own so that waiting for one can time out.
+    replies: Receiver<String>,
+    timeout: Duration,
+    /// Why the process was stopped, after which every value fails.
+    failure: Option<String>,
+    /// Labels by value, so that each distinct value goes through the process once.
+    cache: HashMap<String, String>,
+}
+
+impl ProcessTranslator {
+    pub fn spawn(path: &str) -> Result<Self, String> {
+        Self::start(path, Command::new(path), REPLY_TIMEOUT)
+    }
+
+    fn start(path: &str, mut command: Command, timeout: Duration) -> Result<Self, String> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start translator {}: {}", path, e))?;
+        let stdin = child.stdin.take().ok_or("Translator stdin not available")?;
+        let stdout = child.stdout.take().ok_or("Translator stdout not available")?;
+
+        // Ends when the process closes its stdout, which drops the sender
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(ProcessTranslator {
+            path: path.to_string(),
+            child,
+            stdin,
+            replies,
+            timeout,
+            failure: None,
+            cache: HashMap::new(),
+        })
+    }
+
+    fn request(&mut self, value: &str) -> Result<String, String> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.clone());
+        }
+        // A line nobody asked for would shift every later reply by one
+        if self.replies.try_recv().is_ok() {
+            return Err(self.stop(format!("Translator {} wrote more than one line for a value", self.path)));
+        }
+
+        let error = |e: std::io::Error| format!("Translator {} failed: {}", self.path, e);
+        writeln!(self.stdin, "{}", value).map_err(error)?;
+        self.stdin.flush().map_err(error)?;
+
+        match self.replies.recv_timeout(self.timeout) {
+            Ok(line) => Ok(line.trim_end_matches('\r').to_string()),
+            Err(RecvTimeoutError::Timeout) => {
+                Err(self.stop(format!("Translator {} did not answer within {:?}", self.path, self.timeout)))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(self.stop(format!("Translator {} exited", self.path))),
+        }
+    }
+
+    /// Kills the process and fails every later value with `failure`.
+    fn stop(&mut self, failure: String) -> String {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.failure = Some(failure.clone());
+        failure
+    }
+}
+
+impl ValueTranslator for ProcessTranslator {
+    fn translate(&mut self, value: &str) -> Result<String, String> {
+        if let Some(label) = self.cache.get(value) {
+            return Ok(label.clone());
+        }
+        let label = self.request(value)?;
+        self.cache.insert(value.to_string(), label.clone());
+        Ok(label)
+    }
+}
+
+impl Drop for ProcessTranslator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_echo_translator() {
+        let mut translator = ProcessTranslator::start("cat", Command::new("cat"), REPLY_TIMEOUT).unwrap();
+        assert_eq!(translator.translate("0101").unwrap(), "0101");
+        assert_eq!(translator.translate("1111").unwrap(), "1111");
+    }
+
+    #[test]
+    fn test_silent_translator_times_out() {
+        let mut command = Command::new("sleep");
+        command.arg("60");
+        let timeout = Duration::from_millis(100);
+        let mut translator = ProcessTranslator::start("sleep", command, timeout).unwrap();
+
+        let started = Instant::now();
+        let error = translator.translate("0101").unwrap_err();
+        assert!(error.contains("did not answer"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // The process is gone and later values fail at once
+        assert!(translator.child.try_wait().unwrap().is_some());
+        assert_eq!(translator.translate("1111").unwrap_err(), error);
+    }
+}
@@ -175,9 +175,25 @@ export const removeFile = async (path: string) => {
 }
 
 
+export interface VarIndexRange {
+    msb: number;
+    lsb: number;
+}
+
 export interface HierarchyVar {
     name: string;
     ref: number;
+    /** Number of bits, null for real and string vars */
+    width: number | null;
+    var_type: string;
+    direction: string;
+    index: VarIndexRange | null;
+    encoding: 'bit_vector' | 'real' | 'string';
+    /** Source expression, only set on virtual signals and buses grouped from bit vars */
+    expression?: string;
+    /** Translation table labelling the values, when one is attached */
+    translation?: string;
+    is_alias: boolean;
 }
 
 export interface HierarchyScope {
@@ -187,6 +203,51 @@ export interface HierarchyScope {
     scopes: HierarchyScope[];
 }
 
+export interface ScopeSummary {
+    name: string;
+    ref: number;
+    scope_type: string;
+    scope_count: number;
+    var_count: number;
+    has_children: boolean;
+}
+
+/** One page of a scope's direct children: child scopes first, then vars */
+export interface ScopeChildren {
+    scopes: ScopeSummary[];
+    vars: HierarchyVar[];
+    total_scopes: number;
+    total_vars: number;
+}
+
+export interface SearchQuery {
+    pattern: string;
+    /** Defaults to 'glob'. A glob without a '.' matches local var names only */
+    mode?: 'glob' | 'regex' | 'fuzzy';
+    case_sensitive?: boolean;
+    var_types?: string[];
+    directions?: string[];
+    min_width?: number;
+    max_width?: number;
+}
+
+export interface SearchMatch {
+    path: string;
+    name: string;
+    var_ref: number;
+    ref: number;
+    width: number | null;
+    var_type: string;
+    direction: string;
+    /** Fuzzy match score, null for glob and regex queries */
+    score: number | null;
+}
+
+export interface SearchResults {
+    matches: SearchMatch[];
+    total: number;
+}
+
 export interface HierarchyRoot {
     name: string;
     ref: number;
@@ -194,11 +255,262 @@ export interface HierarchyRoot {
     scopes: HierarchyScope[];
 }
 
+export interface TimescaleInfo {
+    factor: number;
+    unit: string;
+    exponent: number | null;
+}
+
+export interface WaveformInfo {
+    timescale: TimescaleInfo | null;
+    start_time: number | null;
+    end_time: number | null;
+    file_format: string;
+    date: string;
+    version: string;
+    var_count: number;
+    scope_count: number;
+    signal_count: number;
+}
+
+/** Rendering of bit-vector values; reals and strings are always shown as-is */
+export type ValueFormat =
+    | { kind: 'binary' | 'hex' | 'octal' | 'unsigned' | 'signed' | 'ascii' | 'float16' | 'float32' | 'float64' }
+    /** Qm.n fixed point with n = frac_bits */
+    | { kind: 'fixed'; frac_bits: number; signed?: boolean };
+
 export interface SignalChange {
     time: number;
     value: string;
 }
 
+/** Clock edges that start a cycle; both is for double data rate */
+export type ClockEdge = 'rising' | 'falling' | 'both';
+
+export interface CycleAxisInfo {
+    /** Number of clock edges, the last of which starts the last cycle */
+    cycles: number;
+    first_edge: number;
+    last_edge: number;
+}
+
+export interface SampleQuery {
+    clock_id: number;
+    edge?: ClockEdge;
+    /** The columns of the table */
+    signal_ids: number[];
+    /** Edges in [start, end] give the rows, the whole trace by default */
+    start?: number;
+    end?: number;
+    offset?: number;
+    limit: number;
+    format?: ValueFormat;
+}
+
+export interface SampledRow {
+    time: number;
+    /** Number of the edge from the start of the trace, as on the cycle axis */
+    cycle: number;
+    /** One value per column, null before a signal's first change */
+    values: (string | null)[];
+}
+
+export interface SampledTable {
+    rows: SampledRow[];
+    total_rows: number;
+}
+
+export interface EventListQuery {
+    /** The columns of the list */
+    signal_ids: number[];
+    /** Forward pages start at this time, backward pages end just before it */
+    cursor?: number;
+    direction?: NavigationDirection;
+    limit: number;
+    format?: ValueFormat;
+}
+
+export interface EventRow {
+    time: number;
+    /** Columns that change at this time */
+    changed: number[];
+    /** Value of every column from this time on, null before a signal's first change */
+    values: (string | null)[];
+}
+
+export interface EventListPage {
+    /** In time order, whichever the direction */
+    rows: EventRow[];
+    /** Pass forward for the next page, null at the end of the trace */
+    next_cursor: number | null;
+    /** Pass backward for the previous page, null at the start of the trace */
+    previous_cursor: number | null;
+}
+
+export interface NearbyChange {
+    signal_id: number;
+    path: string;
+    /** Time of the transition closest to the queried time */
+    time: number;
+    distance: number;
+    /** null for the signal's first value */
+    old_value: string | null;
+    new_value: string;
+    /** Number of transitions of the signal in the window */
+    transitions: number;
+}
+
+export interface NearbyChanges {
+    /** Closest first */
+    changes: NearbyChange[];
+    /** Number of vars with a transition in the window */
+    total: number;
+}
+
+export type ActivitySource =
+    | { kind: 'signal'; signal_id: number }
+    | { kind: 'scope'; scope_ref: number | null };
+
+export interface ActivityMap {
+    /** The buckets split [start, end] into equal spans */
+    start: number;
+    end: number;
+    /** Transitions in each bucket, summed over the signals */
+    transitions: number[];
+    /** Number of signals counted */
+    signals: number;
+}
+
+/** Span where a clock stopped toggling */
+export interface GatedInterval {
+    start: number;
+    end: number;
+}
+
+export interface ClockInfo {
+    signal_id: number;
+    path: string;
+    /** Mean period while running, in file time units */
+    period: number;
+    /** In Hz, null when the file has no known timescale */
+    frequency: number | null;
+    duty_cycle: number;
+    /** Shortest and longest periods while running, whose difference is the jitter */
+    min_period: number;
+    max_period: number;
+    /** Number of rising edges */
+    cycles: number;
+    gated: GatedInterval[];
+}
+
+export interface StableInterval {
+    start: number;
+    end: number;
+    value: string;
+}
+
+export interface SignalStatistics {
+    /** Value changes strictly inside the window */
+    transitions: number;
+    /** Time at 1 and at 0, null unless the signal is 1 bit wide */
+    high_time: number | null;
+    low_time: number | null;
+    duty_cycle: number | null;
+    /** Time with any X or Z bit */
+    unknown_time: number;
+    distinct_values: number;
+    /** For numeric buses and reals; the mean is weighted by time */
+    min: number | null;
+    max: number | null;
+    mean: number | null;
+    longest_stable: StableInterval | null;
+}
+
+/** Step holds each value until the next sample, linear draws a line between samples */
+export type Interpolation = 'step' | 'linear';
+
+export interface AnalogSample {
+    time: number;
+    /** null where a bit vector has unknown bits */
+    value: number | null;
+}
+
+export interface AnalogWindow {
+    interpolation: Interpolation;
+    /** Range of the values shown in the window, to scale the plot */
+    min: number | null;
+    max: number | null;
+    /** True when samples only keeps the extremes of each span */
+    decimated: boolean;
+    /** Starts with the sample holding at the window start and ends with the one after the window */
+    samples: AnalogSample[];
+}
+
+export interface SummaryBucket {
+    start: number;
+    end: number;
+    /** Number of changes inside [start, end) */
+    transitions: number;
+    busy: boolean;
+    /** Value held across the whole bucket, null when busy or before the first change */
+    value: string | null;
+    min: number | null;
+    max: number | null;
+}
+
+/**
+ * Signal changes decoded from the backend's packed binary layout.
+ * Bit vector rows hold `width` states of `bitsPerState` bits each, msb first,
+ * packed from the high bits of each byte (codes 0, 1, x, z, h, u, w, l, -).
+ */
+export type PackedSignalChanges =
+    | { kind: 'bits'; times: BigUint64Array; width: number; bitsPerState: number; rowBytes: number; values: Uint8Array }
+    | { kind: 'real'; times: BigUint64Array; values: Float64Array }
+    | { kind: 'string'; times: BigUint64Array; values: string[] };
+
+export const decodePackedSignalChanges = (buffer: ArrayBuffer): PackedSignalChanges => {
+    const view = new DataView(buffer);
+    const kind = view.getUint8(0);
+    const bitsPerState = view.getUint8(1);
+    const width = view.getUint32(4, true);
+    const count = view.getUint32(8, true);
+    const times = new BigUint64Array(buffer, 16, count);
+    const payload = 16 + count * 8;
+
+    if (kind === 0) {
+        const rowBytes = Math.ceil(width * bitsPerState / 8);
+        return { kind: 'bits', times, width, bitsPerState, rowBytes, values: new Uint8Array(buffer, payload, count * rowBytes) };
+    }
+    if (kind === 1) {
+        return { kind: 'real', times, values: new Float64Array(buffer, payload, count) };
+    }
+
+    const decoder = new TextDecoder();
+    const values: string[] = [];
+    let offset = payload;
+    for (let i = 0; i < count; i++) {
+        const length = view.getUint32(offset, true);
+        values.push(decoder.decode(new Uint8Array(buffer, offset + 4, length)));
+        offset += 4 + length;
+    }
+    return { kind: 'string', times, values };
+};
+
+export interface ValueAt {
+    /** null before the signal's first change */
+    value: string | null;
+    previous_change: number | null;
+    next_change: number | null;
+}
+
+export type NavigationDirection = 'forward' | 'backward';
+
+/** Which change to jump to; changes that repeat the previous value never match */
+export type NavigationTarget =
+    | { kind: 'change' | 'rising_edge' | 'falling_edge' | 'unknown' }
+    /** Bit vectors take a literal such as `8'hff`, `0x1f` or `12` */
+    | { kind: 'equals' | 'not_equals'; value: string };
+
 export const getHierarchy = async (filename: string): Promise<HierarchyRoot> => {
     if (isTauri) {
         return await invoke("get_hierarchy", { filename });
@@ -206,22 +518,407 @@ export const getHierarchy = async (filename: string): Promise<HierarchyRoot> =>
     return wasm.get_hierarchy_wasm(filename);
 };
 
-export const getSignalChanges = async (filename: string, signalId: number, start: number, end: number): Promise<SignalChange[]> => {
+/**
+ * List one level of the hierarchy
+ * @param scopeRef - Scope to expand, or null for the top-level items
+ */
+export const getScopeChildren = async (filename: string, scopeRef: number | null, offset: number, limit: number): Promise<ScopeChildren> => {
+    if (isTauri) {
+        return await invoke("get_scope_children", { filename, scopeRef, offset, limit });
+    }
+    return wasm.get_scope_children_wasm(filename, scopeRef ?? undefined, offset, limit);
+};
+
+export const searchSignals = async (filename: string, query: SearchQuery, offset: number, limit: number): Promise<SearchResults> => {
+    if (isTauri) {
+        return await invoke("search_signals", { filename, query, offset, limit });
+    }
+    return wasm.search_signals_wasm(filename, query, offset, limit);
+};
+
+export const getWaveformInfo = async (filename: string): Promise<WaveformInfo> => {
+    if (isTauri) {
+        return await invoke("get_waveform_info", { filename });
+    }
+    return wasm.get_waveform_info_wasm(filename);
+};
+
+export const getSignalChanges = async (filename: string, signalId: number, start: number, end: number, format?: ValueFormat): Promise<SignalChange[]> => {
     // Note: Rust u64 might come back as number or BigInt depending on bindings.
     // Usually standard JSON keeps it as number (potential precision loss).
     if (isTauri) {
         // Tauri invoke passes arguments as JSON.
         // Rust accepts `signal_id` snake_case by default for serde structs, but arguments to commands usage depends on Tauri.
         // Tauri 2.0 usually camelCase arguments in invoke map to snake_case in Rust function arguments.
-        return await invoke("get_signal_changes", { filename, signalId, start, end });
+        return await invoke("get_signal_changes", { filename, signalId, start, end, format });
     }
     // wasm-bindgen uses direct args
     // However, JS numbers for u64 might be risky. BigInt might be required.
     // Round to integers before converting to BigInt since time values may be floating-point
     // from zoom/pan operations
+    if (format) {
+        return wasm.get_signal_changes_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)), format);
+    }
     return wasm.get_signal_changes_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)));
 };
 
+/**
+ * Same window as getSignalChanges, transferred as typed arrays instead of one object per change
+ */
+export const getSignalChangesPacked = async (filename: string, signalId: number, start: number, end: number): Promise<PackedSignalChanges> => {
+    if (isTauri) {
+        const buffer: ArrayBuffer = await invoke("get_signal_changes_packed", { filename, signalId, start, end });
+        return decodePackedSignalChanges(buffer);
+    }
+    const bytes: Uint8Array = wasm.get_signal_changes_packed_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)));
+    // Copy into a fresh buffer so the 8-byte aligned views line up
+    return decodePackedSignalChanges(bytes.slice().buffer);
+};
+
+/**
+ * Fetch the changes of several signals over the same window in one call
+ * @returns Changes keyed by signal id
+ */
+export const getSignalChangesBatch = async (filename: string, signalIds: number[], start: number, end: number, format?: ValueFormat): Promise<Record<number, SignalChange[]>> => {
+    if (isTauri) {
+        return await invoke("get_signal_changes_batch", { filename, signalIds, start, end, format });
+    }
+    return wasm.get_signal_changes_batch_wasm(filename, new Uint32Array(signalIds), BigInt(Math.round(start)), BigInt(Math.round(end)), format);
+};
+
+/**
+ * Summarise a signal over equal time buckets, e.g. one per pixel column
+ */
+export const getSignalSummary = async (filename: string, signalId: number, start: number, end: number, buckets: number): Promise<SummaryBucket[]> => {
+    if (isTauri) {
+        return await invoke("get_signal_summary", { filename, signalId, start, end, buckets });
+    }
+    return wasm.get_signal_summary_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)), buckets);
+};
+
+/** Set up a time axis counted in cycles of a clock, cycle n starting at its nth edge from 0 */
+export const getCycleAxis = async (filename: string, clockId: number, edge: ClockEdge): Promise<CycleAxisInfo> => {
+    if (isTauri) {
+        return await invoke("get_cycle_axis", { filename, clockId, edge });
+    }
+    return wasm.get_cycle_axis_wasm(filename, clockId, edge);
+};
+
+/** Convert times, such as navigation results or search matches, to fractional cycles */
+export const timesToCycles = async (filename: string, clockId: number, edge: ClockEdge, times: number[]): Promise<number[]> => {
+    if (isTauri) {
+        return await invoke("times_to_cycles", { filename, clockId, edge, times });
+    }
+    const result = wasm.times_to_cycles_wasm(filename, clockId, edge, new BigUint64Array(times.map(t => BigInt(Math.round(t)))));
+    return Array.from(result);
+};
+
+/** Convert cycle positions to times, e.g. the ends of a cycle range to a window for getSignalChanges */
+export const cyclesToTimes = async (filename: string, clockId: number, edge: ClockEdge, cycles: number[]): Promise<number[]> => {
+    if (isTauri) {
+        return await invoke("cycles_to_times", { filename, clockId, edge, cycles });
+    }
+    const result = wasm.cycles_to_times_wasm(filename, clockId, edge, new Float64Array(cycles));
+    return Array.from(result, Number);
+};
+
+/**
+ * Sample signals at every edge of a clock, one row per edge. Each edge sees the values held just
+ * before it, like a flip-flop. Pass the next offset to get the next page
+ */
+export const sampleOnClock = async (filename: string, query: SampleQuery): Promise<SampledTable> => {
+    if (isTauri) {
+        return await invoke("sample_on_clock", { filename, query });
+    }
+    return wasm.sample_on_clock_wasm(filename, query);
+};
+
+/**
+ * List every time at which any of the signals changes, with the values of all of them. Pass
+ * next_cursor forward or previous_cursor backward to get the neighbouring pages
+ */
+export const getEventList = async (filename: string, query: EventListQuery): Promise<EventListPage> => {
+    if (isTauri) {
+        return await invoke("get_event_list", { filename, query });
+    }
+    return wasm.get_event_list_wasm(filename, query);
+};
+
+/** Export the list of [start, end] as CSV, with a time column and one column per signal path */
+export const exportEventListCsv = async (filename: string, signalIds: number[], start: number, end?: number, format?: ValueFormat): Promise<string> => {
+    if (isTauri) {
+        return await invoke("export_event_list_csv", { filename, signalIds, start, end, format });
+    }
+    return wasm.export_event_list_csv_wasm(
+        filename,
+        new Uint32Array(signalIds),
+        BigInt(Math.round(start)),
+        end === undefined ? undefined : BigInt(Math.round(end)),
+        format,
+    );
+};
+
+/**
+ * Find the vars under a scope, or in the whole file when scopeRef is null, whose signal changes
+ * within radius of time, closest first. Reads every signal of the subtree
+ */
+export const getChangesNear = async (filename: string, scopeRef: number | null, time: number, radius: number, limit: number, format?: ValueFormat): Promise<NearbyChanges> => {
+    if (isTauri) {
+        return await invoke("get_changes_near", { filename, scopeRef, time, radius, limit, format });
+    }
+    return wasm.get_changes_near_wasm(filename, scopeRef ?? undefined, BigInt(Math.round(time)), BigInt(Math.round(radius)), limit, format);
+};
+
+/**
+ * Count the transitions of a signal, or of every signal under a scope (the whole file for a
+ * null scope_ref), in `buckets` equal spans of the trace, for the minimap and the hierarchy tree.
+ * The bucket count is capped at 4096 and at the number of times in the trace. The backend keeps
+ * the latest result of each source
+ */
+export const getActivityMap = async (filename: string, source: ActivitySource, buckets: number): Promise<ActivityMap> => {
+    if (isTauri) {
+        return await invoke("get_activity_map", { filename, source, buckets });
+    }
+    return wasm.get_activity_map_wasm(filename, source, buckets);
+};
+
+/** Find the clock-like signals of a file, loading all its 1-bit signals the first time */
+export const detectClocks = async (filename: string): Promise<ClockInfo[]> => {
+    if (isTauri) {
+        return await invoke("detect_clocks", { filename });
+    }
+    return wasm.detect_clocks_wasm(filename);
+};
+
+/** Clock analysis of one signal, null when it does not behave like a clock */
+export const getClockInfo = async (filename: string, signalId: number): Promise<ClockInfo | null> => {
+    if (isTauri) {
+        return await invoke("get_clock_info", { filename, signalId });
+    }
+    return wasm.get_clock_info_wasm(filename, signalId);
+};
+
+/** Figures of a signal over [start, end), in file time units */
+export const getSignalStatistics = async (filename: string, signalId: number, start: number, end: number): Promise<SignalStatistics> => {
+    if (isTauri) {
+        return await invoke("get_signal_statistics", { filename, signalId, start, end });
+    }
+    return wasm.get_signal_statistics_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)));
+};
+
+/**
+ * Read a real signal, or a bit vector as an unsigned number, as numbers for plotting. With maxSamples,
+ * denser windows keep only the lowest and highest sample of each span, so peaks survive decimation
+ */
+export const getAnalogSamples = async (filename: string, signalId: number, start: number, end: number, maxSamples?: number): Promise<AnalogWindow> => {
+    if (isTauri) {
+        return await invoke("get_analog_samples", { filename, signalId, start, end, maxSamples });
+    }
+    return wasm.get_analog_samples_wasm(filename, signalId, BigInt(Math.round(start)), BigInt(Math.round(end)), maxSamples);
+};
+
+export const setInterpolation = async (filename: string, signalId: number, interpolation: Interpolation): Promise<void> => {
+    if (isTauri) {
+        return await invoke("set_interpolation", { filename, signalId, interpolation });
+    }
+    return wasm.set_interpolation_wasm(filename, signalId, interpolation);
+};
+
+/**
+ * Read the value of each signal at a given time, e.g. under the cursor
+ * @returns Values keyed by signal id
+ */
+export const getValuesAt = async (filename: string, signalIds: number[], time: number, format?: ValueFormat): Promise<Record<number, ValueAt>> => {
+    if (isTauri) {
+        return await invoke("get_values_at", { filename, signalIds, time, format });
+    }
+    return wasm.get_values_at_wasm(filename, new Uint32Array(signalIds), BigInt(Math.round(time)), format);
+};
+
+/**
+ * Find the first matching change after `from`, or the last one before it when searching backward
+ * @returns The time of the change, or null if there is none
+ */
+export const findSignalChange = async (filename: string, signalId: number, from: number, direction: NavigationDirection, target: NavigationTarget): Promise<number | null> => {
+    if (isTauri) {
+        return await invoke("find_signal_change", { filename, signalId, from, direction, target });
+    }
+    // None comes back as undefined from wasm
+    return wasm.find_signal_change_wasm(filename, signalId, BigInt(Math.round(from)), direction, target) ?? null;
+};
+
+/**
+ * Add a signal computed from an expression such as `top.valid & top.ready` or `{hi, lo}`
+ * @returns The new var, listed at the top level of the hierarchy; its ref works with every signal query
+ */
+export const addVirtualSignal = async (filename: string, name: string, expression: string): Promise<HierarchyVar> => {
+    if (isTauri) {
+        return await invoke("add_virtual_signal", { filename, name, expression });
+    }
+    return wasm.add_virtual_signal_wasm(filename, name, expression);
+};
+
+/** Named `[msb:lsb]` range of a bus, in the bus's declared indices */
+export interface BusField {
+    name: string;
+    msb: number;
+    lsb: number;
+}
+
+/**
+ * Add a virtual signal for each field of a bus, named after the field. Either every field is added or none is
+ */
+export const addBusFields = async (filename: string, signalId: number, fields: BusField[]): Promise<HierarchyVar[]> => {
+    if (isTauri) {
+        return await invoke("add_bus_fields", { filename, signalId, fields });
+    }
+    return wasm.add_bus_fields_wasm(filename, signalId, fields);
+};
+
+/**
+ * Add a virtual signal concatenating signals, most significant first, such as the bit vars of a bus.
+ * Buses whose bits are dumped as sibling `name[N]` vars are already grouped in the hierarchy
+ */
+export const assembleBus = async (filename: string, name: string, signalIds: number[]): Promise<HierarchyVar> => {
+    if (isTauri) {
+        return await invoke("assemble_bus", { filename, name, signalIds });
+    }
+    return wasm.assemble_bus_wasm(filename, name, new Uint32Array(signalIds));
+};
+
+/** GTKWave translate filter file, or a JSON object from values to labels */
+export type TranslationFormat = 'filter' | 'json';
+
+/**
+ * Add a value-to-label table, replacing any table of the same name. Values are hex unless written
+ * as Verilog literals (`4'b0110`) or with a 0x/0o/0b prefix
+ * @returns The number of entries
+ */
+export const addTranslationTable = async (filename: string, name: string, content: string, format: TranslationFormat): Promise<number> => {
+    if (isTauri) {
+        return await invoke("add_translation_table", { filename, name, content, format });
+    }
+    return wasm.add_translation_table_wasm(filename, name, content, format);
+};
+
+/** Names of the file's translation tables, including one per VHDL enum type */
+export const getTranslationTables = async (filename: string): Promise<string[]> => {
+    if (isTauri) {
+        return await invoke("get_translation_tables", { filename });
+    }
+    return wasm.get_translation_tables_wasm(filename);
+};
+
+/**
+ * Attach a translation table so that the signal's values come back as labels, or detach it with null
+ */
+export const setSignalTranslation = async (filename: string, signalId: number, table: string | null): Promise<void> => {
+    if (isTauri) {
+        return await invoke("set_signal_translation", { filename, signalId, table });
+    }
+    return wasm.set_signal_translation_wasm(filename, signalId, table ?? undefined);
+};
+
+/**
+ * Label the signal's values with an external executable, or detach it with null. The process reads
+ * one formatted value per line on stdin and answers with one label per line on stdout, like a
+ * GTKWave transaction filter process. Desktop app only
+ */
+export const setProcessTranslator = async (filename: string, signalId: number, path: string | null): Promise<void> => {
+    if (isTauri) {
+        return await invoke("set_process_translator", { filename, signalId, path });
+    }
+    throw new Error("Process translators are only available in the desktop app");
+};
+
+export const removeVirtualSignal = async (filename: string, signalId: number): Promise<void> => {
+    if (isTauri) {
+        return await invoke("remove_virtual_signal", { filename, signalId });
+    }
+    return wasm.remove_virtual_signal(filename, signalId);
+};
+
+/** Condition over several signals, written with the same syntax as virtual signals */
+export type TemporalCondition =
+    | { kind: 'holds'; expression: string }
+    /** `trigger` rises and `response` is not true within `within` rising edges of `clock`, or time units without one */
+    | { kind: 'unanswered'; trigger: string; response: string; within: number; clock?: string };
+
+export interface TemporalQuery {
+    condition: TemporalCondition;
+    /** Time to search from: 0, then the previous page's next_start */
+    start?: number;
+    /** No match starts after this time */
+    end?: number;
+    /** The page stops after this many matches */
+    max_results: number;
+    /** Id to pass to cancelTemporalSearch */
+    search_id?: number;
+}
+
+export interface TemporalMatch {
+    start: number;
+    /** null when the trace ends first */
+    end: number | null;
+}
+
+export interface TemporalSearchResults {
+    matches: TemporalMatch[];
+    /** null once the whole range has been searched */
+    next_start: number | null;
+}
+
+/**
+ * Find one page of times where a condition over several signals holds
+ */
+export const searchTemporal = async (filename: string, query: TemporalQuery): Promise<TemporalSearchResults> => {
+    if (isTauri) {
+        return await invoke("search_temporal", { filename, query });
+    }
+    return wasm.search_temporal_wasm(filename, query);
+};
+
+/**
+ * Stop the running search with this id; ids of searches that are not running are ignored.
+ * Desktop app only: in the browser a search holds the page's only thread until it is over
+ */
+export const cancelTemporalSearch = async (searchId: number): Promise<void> => {
+    if (isTauri) {
+        return await invoke("cancel_temporal_search", { searchId });
+    }
+    wasm.cancel_temporal_search(searchId);
+};
+
+export type AttemptStatus = 'pass' | 'fail' | 'disabled' | 'incomplete';
+
+export interface AssertionAttempt {
+    /** Clock tick where the attempt starts */
+    start: number;
+    /** Tick where it passes or fails, the last tick of the trace when incomplete */
+    end: number;
+    status: AttemptStatus;
+}
+
+export interface AssertionReport {
+    /** Every attempt, or only the failing ones with failuresOnly */
+    attempts: AssertionAttempt[];
+    passed: number;
+    failed: number;
+    disabled: number;
+    incomplete: number;
+}
+
+/**
+ * Check an assertion such as `@(posedge top.clk) disable iff (top.rst) top.req |-> ##[1:4] top.ack`
+ * at every tick of its clock
+ */
+export const checkAssertion = async (filename: string, property: string, failuresOnly = false): Promise<AssertionReport> => {
+    if (isTauri) {
+        return await invoke("check_assertion", { filename, property, failuresOnly });
+    }
+    return wasm.check_assertion_wasm(filename, property, failuresOnly);
+};
+
 /**
  * Restore files from the previous session (web only)
  * This is automatically called on startup for Tauri, but needs to be manually called for web
@@ -1,4 +1,4 @@
-import { getSignalChanges, SignalChange, getHierarchy } from '../../../backend';
+import { getSignalChanges, SignalChange, getHierarchy, getWaveformInfo } from '../../../backend';
 
 import { css } from '../../../utils/css-utils.js';
 import { setupCanvasForHighDPI } from '../../../utils/canvas-utils.js';
@@ -846,17 +846,16 @@ export class FileDisplay extends HTMLElement {
     return 0;
   }
 
-  private async initializeTimeRange(signalRef: number) {
+  private async initializeTimeRange() {
     if (this.timeRangeInitialized || !this._filename) return;
 
     try {
-      // Use a large but reasonable upper bound to detect actual time range
-      // 1e15 nanoseconds = ~11.5 days which is reasonable for waveform simulations
-      const changes = await getSignalChanges(this._filename, signalRef, 0, 1e15);
+      // The backend reports the first and last entries of the file's time table
+      const info = await getWaveformInfo(this._filename);
 
-      if (changes.length > 0) {
-        this.visibleStart = changes[0].time;
-        this.visibleEnd = changes[changes.length - 1].time;
+      if (info.start_time !== null && info.end_time !== null) {
+        this.visibleStart = info.start_time;
+        this.visibleEnd = info.end_time;
         this.timeRangeInitialized = true;
 
         // Update minimap with total range
@@ -892,7 +891,7 @@ export class FileDisplay extends HTMLElement {
 
     try {
       // Initialize time range from first signal if not yet done
-      await this.initializeTimeRange(signalRef);
+      await this.initializeTimeRange();
 
       // Fetch signal changes using the current visible range
       const changes = await getSignalChanges(